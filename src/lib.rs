@@ -1,24 +1,24 @@
-/// MIT License
-///
-/// Copyright (c) 2023 herrsmitty8128
-///
-/// Permission is hereby granted, free of charge, to any person obtaining a copy
-/// of this software and associated documentation files (the "Software"), to deal
-/// in the Software without restriction, including without limitation the rights
-/// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
-/// copies of the Software, and to permit persons to whom the Software is
-/// furnished to do so, subject to the following conditions:
-///
-/// The above copyright notice and this permission notice shall be included in all
-/// copies or substantial portions of the Software.
-///
-/// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
-/// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
-/// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
-/// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
-/// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
-/// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
-/// SOFTWARE.  
+// MIT License
+//
+// Copyright (c) 2023 herrsmitty8128
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
 
 /// A module for displaying different text styles through stdout on the command line.
 pub mod text {
@@ -61,6 +61,116 @@ pub mod text {
         }
     }
 
+    /// The foreground colors of the 16 color palette as (code, red, green, blue). The RGB values
+    /// are the ones used by xterm and are used to find the closest match for a 24-bit color.
+    const ANSI_PALETTE: [(u8, u8, u8, u8); 16] = [
+        (30, 0, 0, 0),
+        (31, 205, 0, 0),
+        (32, 0, 205, 0),
+        (33, 205, 205, 0),
+        (34, 0, 0, 238),
+        (35, 205, 0, 205),
+        (36, 0, 205, 205),
+        (37, 229, 229, 229),
+        (90, 127, 127, 127),
+        (91, 255, 0, 0),
+        (92, 0, 255, 0),
+        (93, 255, 255, 0),
+        (94, 92, 92, 255),
+        (95, 255, 0, 255),
+        (96, 0, 255, 255),
+        (97, 255, 255, 255),
+    ];
+
+    /// The colors a terminal is able to display.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ColorSupport {
+        /// 24-bit RGB colors.
+        TrueColor,
+        /// The 16 color palette, where each color is displayed as the closest palette entry.
+        Ansi16,
+    }
+
+    /// Returns the colors the terminal is able to display. Truecolor support is detected using the
+    /// ```COLORTERM``` environment variable. All other terminals are assumed to support 16 colors.
+    pub fn color_support() -> ColorSupport {
+        match std::env::var("COLORTERM") {
+            Ok(v) if v == "truecolor" || v == "24bit" => ColorSupport::TrueColor,
+            _ => ColorSupport::Ansi16,
+        }
+    }
+
+    /// A foreground color that can be used with stdout. Unlike ```text::Style```, a color can be any
+    /// 24-bit RGB value, which makes it possible to blend between colors.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Color {
+        /// A 24-bit color.
+        Rgb(u8, u8, u8),
+        /// A color from the 16 color palette, where the value is the SGR code (30-37 or 90-97).
+        Ansi(u8),
+    }
+
+    impl Color {
+        /// Returns the red, green, and blue components of the color. Palette colors are
+        /// converted using the RGB values used by xterm.
+        pub fn to_rgb(self) -> (u8, u8, u8) {
+            match self {
+                Color::Rgb(r, g, b) => (r, g, b),
+                Color::Ansi(code) => ANSI_PALETTE
+                    .iter()
+                    .find(|(c, ..)| *c == code)
+                    .map(|(_, r, g, b)| (*r, *g, *b))
+                    .unwrap_or((229, 229, 229)),
+            }
+        }
+
+        /// Returns the color that is ```t``` (0.0 <= t <= 1.0) of the way between ```self``` and ```other```.
+        pub fn lerp(self, other: Color, t: f32) -> Color {
+            let t: f32 = t.clamp(0.0, 1.0);
+            let (r1, g1, b1) = self.to_rgb();
+            let (r2, g2, b2) = other.to_rgb();
+            let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+            Color::Rgb(mix(r1, r2), mix(g1, g2), mix(b1, b2))
+        }
+
+        /// Returns the closest color in the 16 color palette. Saturated colors are only matched
+        /// against the chromatic entries of the palette so that they never fade to gray.
+        pub fn to_ansi16(self) -> Color {
+            let (r, g, b) = self.to_rgb();
+            let saturated: bool = r.max(g).max(b) - r.min(g).min(b) > 64;
+            let distance = |(_, pr, pg, pb): &&(u8, u8, u8, u8)| {
+                let dr: i32 = r as i32 - *pr as i32;
+                let dg: i32 = g as i32 - *pg as i32;
+                let db: i32 = b as i32 - *pb as i32;
+                dr * dr + dg * dg + db * db
+            };
+            let (code, ..) = ANSI_PALETTE
+                .iter()
+                .filter(|(c, ..)| !saturated || !matches!(c, 30 | 37 | 90 | 97))
+                .min_by_key(distance)
+                .unwrap();
+            Color::Ansi(*code)
+        }
+
+        /// Returns the color as it should be displayed by a terminal with the given ```support```.
+        pub fn for_support(self, support: ColorSupport) -> Color {
+            match support {
+                ColorSupport::TrueColor => self,
+                ColorSupport::Ansi16 => self.to_ansi16(),
+            }
+        }
+    }
+
+    impl Display for Color {
+        /// Formats a text::Color as a string so it can printed to stdout
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Color::Rgb(r, g, b) => write!(f, "\x1b[38;2;{};{};{}m", r, g, b),
+                Color::Ansi(code) => write!(f, "\x1b[{}m", code),
+            }
+        }
+    }
+
     /// Prints a list of all text::Styles to stdout.
     pub fn print_samples() {
         println!("{}Style::Regular{}", Style::Regular, Style::Regular);
@@ -96,25 +206,415 @@ pub mod text {
         println!("{}Style::WhiteBg{}", Style::WhiteBg, Style::Regular);
         println!("{}Style::Red{}", Style::Red, Style::Regular);
     }
+
+    /// Returns the number of terminal columns that a single char occupies when printed. Control
+    /// characters and zero-width characters (combining marks, joiners, and variation selectors)
+    /// occupy 0 columns, East Asian wide characters and most emoji occupy 2 columns, and every
+    /// other char occupies 1 column.
+    pub fn char_width(c: char) -> usize {
+        match c as u32 {
+            0x00..=0x1F | 0x7F..=0x9F => 0,
+            0x0300..=0x036F
+            | 0x1AB0..=0x1AFF
+            | 0x1DC0..=0x1DFF
+            | 0x200B..=0x200F
+            | 0x20D0..=0x20FF
+            | 0xFE00..=0xFE0F
+            | 0xFE20..=0xFE2F
+            | 0xE0100..=0xE01EF => 0,
+            0x1100..=0x115F
+            | 0x231A..=0x231B
+            | 0x2329..=0x232A
+            | 0x23E9..=0x23EC
+            | 0x23F0
+            | 0x23F3
+            | 0x25FD..=0x25FE
+            | 0x2614..=0x2615
+            | 0x2648..=0x2653
+            | 0x267F
+            | 0x2693
+            | 0x26A1
+            | 0x26AA..=0x26AB
+            | 0x26BD..=0x26BE
+            | 0x26C4..=0x26C5
+            | 0x26CE
+            | 0x26D4
+            | 0x26EA
+            | 0x26F2..=0x26F3
+            | 0x26F5
+            | 0x26FA
+            | 0x26FD
+            | 0x2705
+            | 0x270A..=0x270B
+            | 0x2728
+            | 0x274C
+            | 0x274E
+            | 0x2753..=0x2755
+            | 0x2757
+            | 0x2795..=0x2797
+            | 0x27B0
+            | 0x27BF
+            | 0x2B1B..=0x2B1C
+            | 0x2B50
+            | 0x2B55
+            | 0x2E80..=0x303E
+            | 0x3041..=0x33FF
+            | 0x3400..=0x4DBF
+            | 0x4E00..=0x9FFF
+            | 0xA000..=0xA4CF
+            | 0xA960..=0xA97F
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE10..=0xFE19
+            | 0xFE30..=0xFE6F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6
+            | 0x1F004
+            | 0x1F0CF
+            | 0x1F18E
+            | 0x1F191..=0x1F19A
+            | 0x1F1E6..=0x1F1FF
+            | 0x1F200..=0x1F202
+            | 0x1F210..=0x1F23B
+            | 0x1F240..=0x1F248
+            | 0x1F250..=0x1F251
+            | 0x1F300..=0x1F320
+            | 0x1F32D..=0x1F335
+            | 0x1F337..=0x1F37C
+            | 0x1F37E..=0x1F393
+            | 0x1F3A0..=0x1F3CA
+            | 0x1F3CF..=0x1F3D3
+            | 0x1F3E0..=0x1F3F0
+            | 0x1F3F4
+            | 0x1F3F8..=0x1F43E
+            | 0x1F440
+            | 0x1F442..=0x1F4FC
+            | 0x1F4FF..=0x1F53D
+            | 0x1F54B..=0x1F54E
+            | 0x1F550..=0x1F567
+            | 0x1F57A
+            | 0x1F595..=0x1F596
+            | 0x1F5A4
+            | 0x1F5FB..=0x1F64F
+            | 0x1F680..=0x1F6C5
+            | 0x1F6CC
+            | 0x1F6D0..=0x1F6D2
+            | 0x1F6D5..=0x1F6D7
+            | 0x1F6EB..=0x1F6EC
+            | 0x1F6F4..=0x1F6FC
+            | 0x1F7E0..=0x1F7EB
+            | 0x1F90C..=0x1F93A
+            | 0x1F93C..=0x1F945
+            | 0x1F947..=0x1F9FF
+            | 0x1FA70..=0x1FAFF
+            | 0x20000..=0x2FFFD
+            | 0x30000..=0x3FFFD => 2,
+            _ => 1,
+        }
+    }
+
+    /// Returns the number of terminal columns that ```s``` occupies when printed. ANSI escape
+    /// sequences, such as the ones produced by ```text::Style```, are skipped and the char
+    /// following a zero width joiner is treated as part of the same emoji.
+    pub fn visible_width(s: &str) -> usize {
+        let mut width: usize = 0;
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\x1b' => skip_escape(&mut chars),
+                '\u{200D}' => {
+                    chars.next();
+                }
+                _ => width += char_width(c),
+            }
+        }
+        width
+    }
+
+    /// Shortens ```s``` to at most ```width``` columns, ending it with '…' if anything was cut
+    /// off. ANSI escape sequences are kept, and if any were kept, ```Style::Regular``` is added
+    /// at the end so that a style does not leak past the shortened text.
+    pub fn truncate(s: &str, width: usize) -> String {
+        if visible_width(s) <= width {
+            return String::from(s);
+        }
+        let mut result: String = String::new();
+        let mut used: usize = 0;
+        let mut styled: bool = false;
+        for (piece, w) in pieces(s) {
+            if piece.starts_with('\x1b') {
+                result.push_str(piece);
+                styled = true;
+            } else if used + w < width {
+                result.push_str(piece);
+                used += w;
+            } else {
+                break;
+            }
+        }
+        if width > 0 {
+            result.push('\u{2026}');
+        }
+        if styled {
+            result.push_str("\x1b[0m");
+        }
+        result
+    }
+
+    /// Breaks ```s``` into lines of at most ```width``` columns. Lines are broken at spaces where
+    /// possible and words that are too long are split. Newlines in ```s``` always start a new
+    /// line. Styles that are active at the end of a line are reset there and applied again at
+    /// the start of the next line.
+    pub fn wrap(s: &str, width: usize) -> Vec<String> {
+        let width: usize = width.max(1);
+        let mut lines: Vec<String> = Vec::new();
+        let mut active: String = String::new();
+        for paragraph in s.split('\n') {
+            let mut line: String = active.clone();
+            let mut line_width: usize = 0;
+            let mut word: Vec<(&str, usize)> = Vec::new();
+            let mut pieces = pieces(paragraph).into_iter().peekable();
+            loop {
+                let piece: Option<(&str, usize)> = pieces.next();
+                if let Some((p, w)) = piece {
+                    if p != " " {
+                        word.push((p, w));
+                        if pieces.peek().is_some() {
+                            continue;
+                        }
+                    }
+                }
+                let word_width: usize = word.iter().map(|&(_, w)| w).sum();
+                if word_width > 0 {
+                    if line_width > 0 && line_width + 1 + word_width > width {
+                        end_line(&mut lines, &mut line, &active);
+                        line_width = 0;
+                    } else if line_width > 0 {
+                        line.push(' ');
+                        line_width += 1;
+                    }
+                }
+                for (p, w) in word.drain(..) {
+                    if p.starts_with('\x1b') {
+                        track_styles(&mut active, p);
+                    } else if line_width > 0 && line_width + w > width {
+                        end_line(&mut lines, &mut line, &active);
+                        line_width = 0;
+                    }
+                    line.push_str(p);
+                    line_width += w;
+                }
+                if piece.is_none() {
+                    break;
+                }
+            }
+            end_line(&mut lines, &mut line, &active);
+        }
+        lines
+    }
+
+    /// Returns ```s``` without ANSI escape sequences, e.g. to write styled text to a file.
+    pub fn strip_escapes(s: &str) -> String {
+        pieces(s)
+            .into_iter()
+            .filter(|(piece, _)| !piece.starts_with('\x1b'))
+            .map(|(piece, _)| piece)
+            .collect()
+    }
+
+    /// A crate-private function that updates ```active```, the style escapes that are in effect,
+    /// with the escapes in ```s```. A reset clears them and any other style is added.
+    pub(crate) fn track_styles(active: &mut String, s: &str) {
+        for (piece, _) in pieces(s) {
+            if piece == "\x1b[0m" || piece == "\x1b[m" {
+                active.clear();
+            } else if piece.starts_with('\x1b') && piece.ends_with('m') {
+                active.push_str(piece);
+            }
+        }
+    }
+
+    /// A private function that adds ```line``` to ```lines``` and starts the next line with the
+    /// ```active``` styles, resetting them at the end of the finished line.
+    fn end_line(lines: &mut Vec<String>, line: &mut String, active: &str) {
+        if !active.is_empty() {
+            line.push_str("\x1b[0m");
+        }
+        lines.push(std::mem::replace(line, String::from(active)));
+    }
+
+    /// A private function that splits ```s``` into ANSI escape sequences and visible chars,
+    /// each with the number of columns it occupies. A char that is joined to the previous one
+    /// by a zero width joiner is part of the same piece.
+    fn pieces(s: &str) -> Vec<(&str, usize)> {
+        let mut pieces: Vec<(&str, usize)> = Vec::new();
+        let mut i: usize = 0;
+        while let Some(c) = s[i..].chars().next() {
+            let rest: &str = &s[i..];
+            let (len, width) = if c == '\x1b' {
+                (escape_len(rest), 0)
+            } else {
+                let mut len: usize = c.len_utf8();
+                while rest[len..].starts_with('\u{200D}') {
+                    len += '\u{200D}'.len_utf8();
+                    len += rest[len..].chars().next().map_or(0, char::len_utf8);
+                }
+                (len, char_width(c))
+            };
+            pieces.push((&rest[..len], width));
+            i += len;
+        }
+        pieces
+    }
+
+    /// A private function that returns the length in bytes of the ANSI escape sequence at the
+    /// start of ```s```, recognizing the same sequences as ```skip_escape()```.
+    fn escape_len(s: &str) -> usize {
+        let bytes: &[u8] = s.as_bytes();
+        match bytes.get(1) {
+            Some(b'[') => bytes[2..]
+                .iter()
+                .position(|b| (0x40..=0x7e).contains(b))
+                .map_or(bytes.len(), |i| i + 3),
+            Some(b']') => {
+                let mut i: usize = 2;
+                while i < bytes.len() {
+                    if bytes[i] == 0x07 {
+                        return i + 1;
+                    }
+                    if bytes[i] == 0x1b && bytes.get(i + 1) == Some(&b'\\') {
+                        return i + 2;
+                    }
+                    i += 1;
+                }
+                bytes.len()
+            }
+            Some(_) => 1 + s[1..].chars().next().map_or(0, char::len_utf8),
+            None => 1,
+        }
+    }
+
+    /// A private function that consumes the remainder of an ANSI escape sequence after the
+    /// initial escape char. Both CSI sequences (```ESC [ ... final```) and OSC sequences
+    /// (```ESC ] ... BEL``` or ```ESC ] ... ESC \```) are recognized.
+    fn skip_escape(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) {
+        match chars.next() {
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('\x40'..='\x7e').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+
+        use super::*;
+
+        #[test]
+        fn visible_width_skips_escapes_and_counts_wide_chars() {
+            assert_eq!(visible_width("abc"), 3);
+            assert_eq!(
+                visible_width(&format!("{}abc{}", Style::Bold, Style::Regular)),
+                3
+            );
+            assert_eq!(visible_width("日本"), 4);
+            assert_eq!(visible_width("e\u{301}"), 1);
+            assert_eq!(visible_width("👨\u{200D}👩"), 2);
+            assert_eq!(visible_width(""), 0);
+        }
+
+        #[test]
+        fn truncate_ends_with_an_ellipsis() {
+            assert_eq!(truncate("hello", 5), "hello");
+            assert_eq!(truncate("hello world", 5), "hell\u{2026}");
+            assert_eq!(truncate("日本語", 4), "日\u{2026}");
+            assert_eq!(truncate("hello", 0), "");
+        }
+
+        #[test]
+        fn truncate_keeps_escapes_and_resets_the_style() {
+            let styled: String = format!("{}hello{}", Style::Bold, Style::Regular);
+            let truncated: String = truncate(&styled, 3);
+            assert_eq!(
+                truncated,
+                format!("{}he\u{2026}{}", Style::Bold, Style::Regular)
+            );
+            assert_eq!(visible_width(&truncated), 3);
+        }
+
+        #[test]
+        fn wrap_breaks_at_spaces_and_splits_long_words() {
+            assert_eq!(wrap("the quick brown fox", 10), ["the quick", "brown fox"]);
+            assert_eq!(wrap("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+            assert_eq!(wrap("a\nb", 10), ["a", "b"]);
+            assert_eq!(wrap("", 10), [""]);
+        }
+
+        #[test]
+        fn wrap_carries_styles_to_the_next_line() {
+            let styled: String = format!("{}aaa bbb{}", Style::Bold, Style::Regular);
+            let lines: Vec<String> = wrap(&styled, 3);
+            assert_eq!(
+                lines,
+                [
+                    format!("{}aaa{}", Style::Bold, Style::Regular),
+                    format!("{}bbb{}", Style::Bold, Style::Regular),
+                ]
+            );
+            assert!(lines.iter().all(|line| visible_width(line) <= 3));
+        }
+
+        #[test]
+        fn strip_escapes_removes_styles() {
+            let styled: String = format!("{}a{}b", Style::Red, Style::Regular);
+            assert_eq!(strip_escapes(&styled), "ab");
+        }
+    }
 }
 
 /// The pbar module contains an implementation of a process bar for use in command
 /// line programs. It can be used in single or multiple threads.
 pub mod pbar {
 
+    use crate::format::HumanDuration;
+    use crate::term::{self, Cursor, CursorGuard, Erase};
     use crate::text::{self, Style};
     use std::fmt;
     use std::fmt::Display;
-    use std::sync::mpsc;
+    use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+    use std::sync::{mpsc, Arc, Mutex};
     use std::{io::Write, thread, time};
 
     /// An enum of unicode characters commonly used in a command line progress bar.
-    #[derive(Debug, Copy, Clone)]
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
     pub enum BarChar {
         NumberSign = 0x0023,
         EqualSign = 0x003D,
         LowLine = 0x005F,
-        FullBlock = 0x2588,  //default leading char
+        FullBlock = 0x2588, //default leading char
+        LeftSevenEighthsBlock = 0x2589,
+        LeftThreeQuartersBlock = 0x258A,
+        LeftFiveEighthsBlock = 0x258B,
+        LeftHalfBlock = 0x258C,
+        LeftThreeEighthsBlock = 0x258D,
+        LeftOneQuarterBlock = 0x258E,
+        LeftOneEighthBlock = 0x258F,
         LightShade = 0x2591, //default trailing char
         MediumShade = 0x2592,
         DarkShade = 0x2593,
@@ -141,23 +641,39 @@ pub mod pbar {
         PlayingCardAceOfSpades = 0x1F0A1,
     }
 
+    /// The partially filled block characters used by a smooth progress bar, ordered from one
+    /// eighth to seven eighths of a cell.
+    const PARTIAL_BLOCKS: [BarChar; 7] = [
+        BarChar::LeftOneEighthBlock,
+        BarChar::LeftOneQuarterBlock,
+        BarChar::LeftThreeEighthsBlock,
+        BarChar::LeftHalfBlock,
+        BarChar::LeftFiveEighthsBlock,
+        BarChar::LeftThreeQuartersBlock,
+        BarChar::LeftSevenEighthsBlock,
+    ];
+
     impl BarChar {
         /// Converts a BarChar into the equivalent char. This function should never
         /// encounter an error, but if it does then '\u{0}' will be returned.
         fn to_char(self) -> char {
             std::char::from_u32(self as u32).unwrap_or('\u{0}')
         }
-    }
 
-    impl From<char> for BarChar {
-        /// Creates and returns a new BarChar object from a char. If the char cannot be
-        /// converted into a BarChar object, then a BarChar::FullBlock object is returned.
-        fn from(c: char) -> Self {
-            match c as i32 {
+        /// A private function that returns the BarChar equivalent to ```c```, if there is one.
+        fn from_char(c: char) -> Option<Self> {
+            Some(match c as i32 {
                 0x0023 => BarChar::NumberSign,
                 0x003D => BarChar::EqualSign,
                 0x005F => BarChar::LowLine,
-                0x2588 => BarChar::FullBlock,  //default leading char
+                0x2588 => BarChar::FullBlock, //default leading char
+                0x2589 => BarChar::LeftSevenEighthsBlock,
+                0x258A => BarChar::LeftThreeQuartersBlock,
+                0x258B => BarChar::LeftFiveEighthsBlock,
+                0x258C => BarChar::LeftHalfBlock,
+                0x258D => BarChar::LeftThreeEighthsBlock,
+                0x258E => BarChar::LeftOneQuarterBlock,
+                0x258F => BarChar::LeftOneEighthBlock,
                 0x2591 => BarChar::LightShade, //default trailing char
                 0x2592 => BarChar::MediumShade,
                 0x2593 => BarChar::DarkShade,
@@ -182,8 +698,16 @@ pub mod pbar {
                 0x25FD => BarChar::WhiteMediumSmallSquare,
                 0x25FE => BarChar::BlackMediumSmallSquare,
                 0x1F0A1 => BarChar::PlayingCardAceOfSpades,
-                _ => BarChar::FullBlock,
-            }
+                _ => return None,
+            })
+        }
+    }
+
+    impl From<char> for BarChar {
+        /// Creates and returns a new BarChar object from a char. If the char cannot be
+        /// converted into a BarChar object, then a BarChar::FullBlock object is returned.
+        fn from(c: char) -> Self {
+            BarChar::from_char(c).unwrap_or(BarChar::FullBlock)
         }
     }
 
@@ -195,11 +719,189 @@ pub mod pbar {
         }
     }
 
+    /// The maximum number of terminal columns a ```Glyph``` can occupy.
+    pub const MAX_GLYPH_WIDTH: usize = 8;
+
+    /// An error returned when a string cannot be used as a ```Glyph```.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum GlyphError {
+        /// The string was empty.
+        Empty,
+        /// The string contained a control character, such as a newline or an escape sequence.
+        ControlChar(char),
+        /// The string would not occupy any columns on the terminal.
+        ZeroWidth,
+        /// The string is wider than ```MAX_GLYPH_WIDTH``` columns. Holds the width of the string.
+        TooWide(usize),
+    }
+
+    impl Display for GlyphError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                GlyphError::Empty => f.write_str("a glyph cannot be empty"),
+                GlyphError::ControlChar(c) => {
+                    write!(f, "a glyph cannot contain the control character {:?}", c)
+                }
+                GlyphError::ZeroWidth => f.write_str("a glyph must be at least one column wide"),
+                GlyphError::TooWide(width) => write!(
+                    f,
+                    "a glyph cannot be wider than {} columns, but this one is {} columns wide",
+                    MAX_GLYPH_WIDTH, width
+                ),
+            }
+        }
+    }
+
+    impl std::error::Error for GlyphError {}
+
+    /// One or more characters that are drawn as a single segment of a progress bar. A glyph can
+    /// be any printable char or grapheme, including multi-column emoji, and its width is measured
+    /// in terminal columns. ```BarChar``` values convert into glyphs for free.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Glyph {
+        text: String,
+        width: usize,
+    }
+
+    impl Glyph {
+        /// Creates a new glyph from ```s```. Returns an error if ```s``` is empty, contains a control
+        /// character, would not occupy at least one column on the terminal, or is wider than
+        /// ```MAX_GLYPH_WIDTH``` columns.
+        pub fn new(s: &str) -> Result<Self, GlyphError> {
+            if s.is_empty() {
+                return Err(GlyphError::Empty);
+            }
+            if let Some(c) = s.chars().find(|c| c.is_control()) {
+                return Err(GlyphError::ControlChar(c));
+            }
+            let width: usize = text::visible_width(s);
+            if width == 0 {
+                return Err(GlyphError::ZeroWidth);
+            }
+            if width > MAX_GLYPH_WIDTH {
+                return Err(GlyphError::TooWide(width));
+            }
+            Ok(Self {
+                text: String::from(s),
+                width,
+            })
+        }
+
+        /// Returns the glyph as a string slice.
+        pub fn as_str(&self) -> &str {
+            &self.text
+        }
+
+        /// Returns the number of terminal columns the glyph occupies.
+        pub fn width(&self) -> usize {
+            self.width
+        }
+
+        /// Returns the glyph as a BarChar if it consists of a single char that is a BarChar.
+        pub fn to_bar_char(&self) -> Option<BarChar> {
+            let mut chars = self.text.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => BarChar::from_char(c),
+                _ => None,
+            }
+        }
+    }
+
+    impl From<BarChar> for Glyph {
+        fn from(c: BarChar) -> Self {
+            let c: char = c.to_char();
+            Self {
+                text: String::from(c),
+                width: text::char_width(c),
+            }
+        }
+    }
+
+    impl TryFrom<char> for Glyph {
+        type Error = GlyphError;
+
+        fn try_from(c: char) -> Result<Self, Self::Error> {
+            Self::new(c.encode_utf8(&mut [0; 4]))
+        }
+    }
+
+    impl std::str::FromStr for Glyph {
+        type Err = GlyphError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            Self::new(s)
+        }
+    }
+
+    impl Display for Glyph {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(&self.text)
+        }
+    }
+
+    /// Determines how a ```Gradient``` picks the color of each cell in the leading part of a progress bar.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub enum GradientMode {
+        /// Every cell has the same color, which moves along the gradient as the percentage grows.
+        Percent,
+        /// Each cell has its own color based on its position in the bar, so the gradient is
+        /// revealed from left to right as the percentage grows.
+        Horizontal,
+    }
+
+    /// A range of colors used to draw the leading part of a progress bar.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Gradient {
+        stops: Vec<text::Color>,
+        mode: GradientMode,
+    }
+
+    impl Gradient {
+        /// Creates a new gradient that blends evenly between each of the colors in ```stops```.
+        pub fn new(stops: Vec<text::Color>, mode: GradientMode) -> Self {
+            Self { stops, mode }
+        }
+
+        /// Creates a new gradient that goes from red to yellow to green.
+        pub fn red_yellow_green(mode: GradientMode) -> Self {
+            Self::new(
+                vec![
+                    text::Color::Rgb(220, 50, 47),
+                    text::Color::Rgb(230, 200, 40),
+                    text::Color::Rgb(60, 190, 70),
+                ],
+                mode,
+            )
+        }
+
+        /// Returns how the gradient picks the color of each cell.
+        pub fn mode(&self) -> GradientMode {
+            self.mode
+        }
+
+        /// Returns the color that is ```t``` (0.0 <= t <= 1.0) of the way along the gradient, or ```None```
+        /// if the gradient does not have any colors.
+        pub fn color_at(&self, t: f32) -> Option<text::Color> {
+            match self.stops.len() {
+                0 => None,
+                1 => Some(self.stops[0]),
+                n => {
+                    let pos: f32 = t.clamp(0.0, 1.0) * (n - 1) as f32;
+                    let i: usize = usize::min(pos.floor() as usize, n - 2);
+                    Some(self.stops[i].lerp(self.stops[i + 1], pos - i as f32))
+                }
+            }
+        }
+    }
+
     /// A message object that can be passed from a thread to a ProgressBar object
     /// through a ```mspc::channel```. The message tells the progress bar to update the
     /// corresponding member variable and then call ```self.show()```. See
-    /// ```ProgressBar::listen()``` for more information.
-    #[derive(Debug, Copy, Clone)]
+    /// ```ProgressBar::listen()``` for more information. ```Message::AddChild(id, parent, label,
+    /// weight)``` adds the sub-task ```id``` below the sub-task ```parent``` if there is one. It is
+    /// sent by ```ChildTracker::add_child()```, which is the only way to get a new ```ChildId```.
+    /// Messages can be cloned but not copied, because some of them own a String or a Gradient.
+    #[derive(Debug, Clone)]
     pub enum Message {
         Percent(f32),
         Label(&'static str),
@@ -210,44 +912,208 @@ pub mod pbar {
         TrailingChar(BarChar),
         Interval(u64),
         TextStyle(text::Style),
+        Smooth(bool),
+        LeadingGlyph(Glyph),
+        TrailingGlyph(Glyph),
+        HeadGlyph(Option<Glyph>),
+        Gradient(Option<Gradient>),
+        LabelStyle(Option<text::Style>),
+        LeadingStyle(Option<text::Style>),
+        TrailingStyle(Option<text::Style>),
+        BracketStyle(Option<text::Style>),
+        PercentStyle(Option<text::Style>),
+        Finish,
+        FinishWithMessage(String),
+        FinishAndClear,
+        Abandon,
+        Fail(String),
+        AbandonOnDrop(bool),
+        Print(String),
+        SteadyTick(Option<u64>),
+        StallThreshold(Option<u64>),
+        ShowElapsed(bool),
+        ShowEta(bool),
+        Pause,
+        Resume,
+        AddChild(ChildId, Option<ChildId>, &'static str, f32),
+        ChildPercent(ChildId, f32),
+        ShowChildren(bool),
+    }
+
+    /// Identifies a sub-task of a ProgressBar. Ids are handed out by ```ProgressBar::add_child()```
+    /// and ```ChildTracker```s, so an id always refers to a sub-task that was added, or is on its
+    /// way to the progress bar through its channel.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+    pub struct ChildId(usize);
+
+    /// A weighted sub-task of a ProgressBar, and of the sub-task at ```parent``` if there is one.
+    /// See ```ProgressBar::add_child()```.
+    #[derive(Debug, Clone)]
+    struct Child {
+        label: &'static str,
+        weight: f32,
+        percent: f32,
+        parent: Option<usize>,
+    }
+
+    /// A handle that a thread can use to report the progress of one of the sub-tasks of a
+    /// ProgressBar through a ```mpsc::channel```. Trackers are created with
+    /// ```ProgressBar::add_child_tracker()``` and ```ChildTracker::add_child()```, so they are always
+    /// bound to a sub-task that exists.
+    #[derive(Debug, Clone)]
+    pub struct ChildTracker {
+        id: ChildId,
+        next_index: Arc<AtomicUsize>,
+        tx: mpsc::Sender<Message>,
+    }
+
+    impl ChildTracker {
+        /// Returns the id of the sub-task.
+        pub fn id(&self) -> ChildId {
+            self.id
+        }
+
+        /// Adds a sub-task below this sub-task and returns a tracker for it. Once this sub-task has
+        /// sub-tasks of its own, its percentage is the weighted average of theirs, in the same way
+        /// as the percentage of the progress bar. Returns an error if the progress bar is no longer
+        /// listening.
+        pub fn add_child(
+            &self,
+            label: &'static str,
+            weight: f32,
+        ) -> Result<ChildTracker, mpsc::SendError<Message>> {
+            let id: ChildId = ChildId(self.next_index.fetch_add(1, Ordering::Relaxed));
+            self.tx
+                .send(Message::AddChild(id, Some(self.id), label, weight))?;
+            Ok(Self {
+                id,
+                next_index: Arc::clone(&self.next_index),
+                tx: self.tx.clone(),
+            })
+        }
+
+        /// Sets the percentage completed of the sub-task as a number 0.0 <= n <= 1.0. Returns an
+        /// error if the progress bar is no longer listening.
+        pub fn set_percent(&self, percent: f32) -> Result<(), mpsc::SendError<Message>> {
+            self.tx.send(Message::ChildPercent(self.id, percent))
+        }
+
+        /// Marks the sub-task as complete. Returns an error if the progress bar is no longer listening.
+        pub fn finish(&self) -> Result<(), mpsc::SendError<Message>> {
+            self.set_percent(1.0)
+        }
+    }
+
+    /// The lifecycle state of a ProgressBar. Once a progress bar leaves the ```InProgress``` state,
+    /// it prints its final rendering and will not be printed again.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub enum State {
+        InProgress,
+        Finished,
+        Abandoned,
+        Failed,
     }
 
-    /// An object used to display a progress bar on the command line.
-    #[derive(Debug, Copy, Clone)]
+    /// An object used to display a progress bar on the command line. Dropping a progress bar that
+    /// has been printed but not finished erases it from the screen, or calls
+    /// ```ProgressBar::abandon()``` if abandon on drop is turned on, so the cursor is never left at
+    /// the end of the bar.
+    ///
+    /// The cursor is hidden while a progress bar is on the screen. The first time a progress bar
+    /// is printed, it calls ```term::install_restore_hooks()```, which installs a panic hook and
+    /// process-wide SIGINT and SIGTERM handlers that show the cursor again before the signal is
+    /// handed on to the program's own handlers. A program that installs its handlers afterwards
+    /// replaces these.
+    #[derive(Debug)]
     pub struct ProgressBar {
         length: u32,
-        leading_char: char,
-        trailing_char: char,
+        leading_glyph: Glyph,
+        trailing_glyph: Glyph,
+        head_glyph: Option<Glyph>,
         show_percentage: bool,
         show_brackets: bool,
         interval: time::Duration,
         percent: f32,
         label: &'static str,
-        prev_text_len: usize,
         text_style: text::Style,
+        smooth: bool,
+        gradient: Option<Gradient>,
+        color_support: text::ColorSupport,
+        label_style: Option<text::Style>,
+        leading_style: Option<text::Style>,
+        trailing_style: Option<text::Style>,
+        bracket_style: Option<text::Style>,
+        percent_style: Option<text::Style>,
+        state: State,
+        drawn: bool,
+        finish_style: text::Style,
+        abandon_style: text::Style,
+        fail_style: text::Style,
+        abandon_on_drop: bool,
+        steady_tick: Option<time::Duration>,
+        stall_threshold: Option<time::Duration>,
+        show_elapsed: bool,
+        show_eta: bool,
+        started: time::Instant,
+        last_update: time::Instant,
+        paused_at: Option<time::Instant>,
+        paused_total: time::Duration,
+        children: Vec<Option<Child>>,
+        next_child: Arc<AtomicUsize>,
+        show_children: bool,
+        prev_lines: usize,
+        cursor: Option<CursorGuard>,
+        output: Output,
+    }
+
+    /// Where a ProgressBar prints itself. Tests print into a buffer instead of stdout, so that
+    /// they can check what was printed.
+    #[derive(Debug, Clone, Default)]
+    enum Output {
+        #[default]
+        Stdout,
+        #[cfg(test)]
+        Buffer(Arc<Mutex<String>>),
+    }
+
+    impl Output {
+        /// Prints ```s``` without a newline.
+        fn print(&self, s: &str) {
+            match self {
+                Output::Stdout => print!("{}", s),
+                #[cfg(test)]
+                Output::Buffer(buf) => buf.lock().unwrap().push_str(s),
+            }
+        }
+
+        /// Flushes stdout, so that everything printed so far is shown.
+        fn flush(&self) {
+            match self {
+                Output::Stdout => std::io::stdout().flush().unwrap(),
+                #[cfg(test)]
+                Output::Buffer(_) => {}
+            }
+        }
     }
 
     impl fmt::Display for ProgressBar {
         /// Formats a progress bar for printing based on its internal configuration.
         fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            let mut s: String = String::from(self.label);
+            let mut s: String = String::new();
+            self.write_styled(&mut s, self.label_style, self.label);
             if self.show_brackets {
-                s.push('[');
-            }
-            let count: u32 = (self.length as f32 * self.percent).round() as u32;
-            for _ in 0..count {
-                s.push(self.leading_char);
-            }
-            for _ in count..self.length {
-                s.push(self.trailing_char);
+                self.write_styled(&mut s, self.bracket_style, "[");
             }
+            self.write_bar(&mut s, self.percent);
             if self.show_brackets {
-                s.push(']');
+                self.write_styled(&mut s, self.bracket_style, "]");
             }
             if self.show_percentage {
-                use std::fmt::Write;
-                write!(s, " {:.1}%", self.percent * 100.0).unwrap();
+                s.push(' ');
+                let percentage: String = format!("{:.1}%", self.percent * 100.0);
+                self.write_styled(&mut s, self.percent_style, &percentage);
             }
+            self.write_stats(&mut s);
             fmt.write_str(&s)
         }
     }
@@ -257,12 +1123,27 @@ pub mod pbar {
         /// - length of the bar, excluding the label and percentage: 50 characters
         /// - leading character (the left side of the bar that represents the percentage complete): BarChar::FullBlock
         /// - trailing character: BarChar::LightShade
+        /// - head character: none
         /// - show the percentage: true
         /// - show the brackets at either end of the bar: false
         /// - time interval between refreshing the toolbar on the screen: 0 milliseconds
         /// - percentage complete: 0%
         /// - text label: "Percent complete "
         /// - text style: text::Style::Regular
+        /// - smooth: false
+        /// - gradient: none
+        /// - color support: detected with text::color_support()
+        /// - label, leading, trailing, bracket, and percentage styles: none (uses the text style)
+        /// - finish style: text::Style::Green
+        /// - abandon style: text::Style::Yellow
+        /// - fail style: text::Style::Red
+        /// - abandon on drop: false
+        /// - steady tick: none (only redraw when a message is received)
+        /// - stall threshold: none
+        /// - show the elapsed time: false
+        /// - show the estimated time remaining: false
+        /// - sub-tasks: none
+        /// - show the sub-tasks below the progress bar: false
         fn default() -> Self {
             Self::new("Percent complete ")
         }
@@ -274,15 +1155,43 @@ pub mod pbar {
         pub fn new(label: &'static str) -> Self {
             Self {
                 length: 50,
-                leading_char: BarChar::FullBlock.to_char(),
-                trailing_char: BarChar::LightShade.to_char(),
+                leading_glyph: Glyph::from(BarChar::FullBlock),
+                trailing_glyph: Glyph::from(BarChar::LightShade),
+                head_glyph: None,
                 show_percentage: true,
                 show_brackets: false,
                 interval: time::Duration::from_millis(0),
                 percent: 0.0,
                 label,
-                prev_text_len: 0,
                 text_style: text::Style::Regular,
+                smooth: false,
+                gradient: None,
+                color_support: text::color_support(),
+                label_style: None,
+                leading_style: None,
+                trailing_style: None,
+                bracket_style: None,
+                percent_style: None,
+                state: State::InProgress,
+                drawn: false,
+                finish_style: text::Style::Green,
+                abandon_style: text::Style::Yellow,
+                fail_style: text::Style::Red,
+                abandon_on_drop: false,
+                steady_tick: None,
+                stall_threshold: None,
+                show_elapsed: false,
+                show_eta: false,
+                started: time::Instant::now(),
+                last_update: time::Instant::now(),
+                paused_at: None,
+                paused_total: time::Duration::ZERO,
+                children: Vec::new(),
+                next_child: Arc::new(AtomicUsize::new(0)),
+                show_children: false,
+                prev_lines: 0,
+                cursor: None,
+                output: Output::Stdout,
             }
         }
 
@@ -296,131 +1205,768 @@ pub mod pbar {
             self.text_style = style;
         }
 
-        /// Returns the length of the progress bar, excluding the label, brackets, and percentage, if any.
-        /// This is the length of the actual bar itself.
-        pub fn length(&self) -> u32 {
-            self.length
+        /// Returns the text::Style used when printing the label, if it differs from the text style.
+        pub fn label_style(&self) -> Option<text::Style> {
+            self.label_style
         }
 
-        /// Sets the length of the progress bar, excluding the label, brackets, and percentage, if any.
-        /// This is the length of the actual bar itself.
-        pub fn set_length(&mut self, length: u32) {
-            self.length = length;
+        /// Sets the text::Style used when printing the label. Pass ```None``` to use the text style.
+        pub fn set_label_style(&mut self, style: Option<text::Style>) {
+            self.label_style = style;
         }
 
-        /// Returns the unicode character that ```self.show()``` uses in the body of the progress bar.
-        /// The leading character comprises the left side of the progress bar that represents the
-        /// portion of the task or activity that has been completed.
-        pub fn leading_char(&self) -> BarChar {
-            BarChar::from(self.leading_char)
+        /// Returns the text::Style used when printing the leading part of the bar, if it differs from the text style.
+        pub fn leading_style(&self) -> Option<text::Style> {
+            self.leading_style
         }
 
-        /// Sets the unicode character that ```self.show()``` uses in the body of the progress bar.
-        /// The leading character comprises the left side of the progress bar that represents the
-        /// portion of the task or activity that has been completed.
-        pub fn set_leading_char(&mut self, c: BarChar) {
-            self.leading_char = c.to_char();
+        /// Sets the text::Style used when printing the leading part of the bar. Pass ```None``` to use
+        /// the text style. A gradient, if any, is applied on top of this style.
+        pub fn set_leading_style(&mut self, style: Option<text::Style>) {
+            self.leading_style = style;
         }
 
-        /// Returns the unicode character that ```self.show()``` uses in the body of the progress bar.
-        /// The trailing character comprises the right side of the progress bar that represents the
-        /// portion of the task or activity that has not been completed.
-        pub fn trailing_char(&self) -> BarChar {
-            BarChar::from(self.trailing_char)
+        /// Returns the text::Style used when printing the trailing part of the bar, if it differs from the text style.
+        pub fn trailing_style(&self) -> Option<text::Style> {
+            self.trailing_style
         }
 
-        /// Sets the unicode character that ```self.show()``` uses in the body of the progress bar.
-        /// The trailing character comprises the right side of the progress bar that represents the
-        /// portion of the task or activity that has not been completed.
-        pub fn set_trail_char(&mut self, c: BarChar) {
-            self.trailing_char = c.to_char();
+        /// Sets the text::Style used when printing the trailing part of the bar. Pass ```None``` to use the text style.
+        pub fn set_trailing_style(&mut self, style: Option<text::Style>) {
+            self.trailing_style = style;
         }
 
-        /// If ```true``` is passed, then ```self.show()``` will print the percentage at the end of the progress bar.
-        pub fn show_percentage(&mut self, show: bool) {
-            self.show_percentage = show;
+        /// Returns the text::Style used when printing the brackets, if it differs from the text style.
+        pub fn bracket_style(&self) -> Option<text::Style> {
+            self.bracket_style
         }
 
-        /// If ```true``` is passed, then ```self.show()``` will print the brackets at the beginning and end of the progress bar.
-        pub fn show_brackets(&mut self, show: bool) {
-            self.show_brackets = show;
+        /// Sets the text::Style used when printing the brackets. Pass ```None``` to use the text style.
+        pub fn set_bracket_style(&mut self, style: Option<text::Style>) {
+            self.bracket_style = style;
         }
 
-        /// Returns the number of millliseconds that ```self.show()``` will sleep after printing the progress bar to stdout.
-        /// The default value is 0 milliseconds. This function is provided as a way of "slowing down" updates to the progress bar.
-        /// It is used primarily for testing or simply to provide a better user experience.
-        pub fn interval(&self) -> time::Duration {
-            self.interval
+        /// Returns the text::Style used when printing the percentage, if it differs from the text style.
+        pub fn percent_style(&self) -> Option<text::Style> {
+            self.percent_style
         }
 
-        /// Sets the number of millliseconds that ```self.show()``` will sleep before returning to its caller.
-        /// The default value is 0 milliseconds. This function is provided as a way of "slowing down" updates to the progress bar.
-        /// It is used primarily for testing or simply to provide a better user experience.
-        pub fn set_interval(&mut self, interval: u64) {
-            self.interval = time::Duration::from_millis(interval);
+        /// Sets the text::Style used when printing the percentage. Pass ```None``` to use the text style.
+        pub fn set_percent_style(&mut self, style: Option<text::Style>) {
+            self.percent_style = style;
         }
 
-        /// Returns the current percentage completed of the progress bar as a number 0.0 <= n <= 1.0.
-        pub fn percent(&self) -> f32 {
-            self.percent
+        /// Returns the text::Style used for the final rendering of a finished progress bar.
+        pub fn finish_style(&self) -> text::Style {
+            self.finish_style
         }
 
-        /// Sets the current percentage completed of the progress bar as a number 0.0 <= n <= 1.0.
-        pub fn set_percent(&mut self, percent: f32) {
-            self.percent = f32::min(percent.abs(), 1.0);
+        /// Sets the text::Style used for the final rendering of a finished progress bar.
+        pub fn set_finish_style(&mut self, style: text::Style) {
+            self.finish_style = style;
         }
 
-        /// Returns the text label to the left of the progress bar
-        pub fn label(&self) -> &'static str {
-            self.label
+        /// Returns the text::Style used for the final rendering of an abandoned progress bar.
+        pub fn abandon_style(&self) -> text::Style {
+            self.abandon_style
         }
 
-        /// Sets the text label to the left of the progress bar
-        pub fn set_label(&mut self, msg: &'static str) {
-            self.label = msg;
+        /// Sets the text::Style used for the final rendering of an abandoned progress bar.
+        pub fn set_abandon_style(&mut self, style: text::Style) {
+            self.abandon_style = style;
         }
 
-        /// A private function used to calculate and save the overall length of the progress bar,
-        /// incuding the label, brackets, and percentage immediately after printing to stdout.
-        /// This value is used to clear the row on the command line immediately prior to printing
-        /// the progress bar to stdout. This improves the appearance of the progress bar and
-        /// ensures that it is displayed properly in the event changes are made it its configuration
-        /// in between printing to stdout.
-        fn save_line_length(&mut self) {
-            let mut n: usize = self.length as usize + self.label.len();
-            if self.show_brackets {
-                n += 2;
-            }
-            if self.show_percentage {
-                n += if self.percent >= 1.0 {
-                    7
-                } else if self.percent >= 0.1 {
-                    6
-                } else {
-                    5
-                }
-            };
-            self.prev_text_len = n
+        /// Returns the text::Style used for the final rendering of a failed progress bar.
+        pub fn fail_style(&self) -> text::Style {
+            self.fail_style
         }
 
-        /// A private function that clears the previous progress bar printed to stdout. This function is
-        /// called by ```ProgressBar::show()``` immediately prior to printing the progress bar to stdout.
-        /// See ```ProgressBar::save_line_length()``` for more information.
-        fn clear_line(&self) {
-            print!("\r");
-            for _ in 0..self.prev_text_len {
-                print!(" ");
-            }
-            print!("\r");
+        /// Sets the text::Style used for the final rendering of a failed progress bar.
+        pub fn set_fail_style(&mut self, style: text::Style) {
+            self.fail_style = style;
         }
 
-        /// Listens for messages on ```rx```, executes the message and calls ```self.show()```.
-        ///
-        /// ```
-        /// use cli_tools::pbar::{Progress, ProgressBar};
-        /// use std::{sync::mpsc, thread};
-        ///
-        /// fn myfunc() {
+        /// Returns ```true``` if dropping the progress bar while it is printed but not finished
+        /// abandons it, or ```false``` if it erases it.
+        pub fn abandon_on_drop(&self) -> bool {
+            self.abandon_on_drop
+        }
+
+        /// Sets whether dropping the progress bar while it is printed but not finished abandons it,
+        /// which prints it one last time followed by "(abandoned)" and moves the cursor to the next
+        /// line. This is turned off by default, so an early return erases the progress bar and its
+        /// sub-tasks and leaves the cursor at the beginning of the now empty line.
+        pub fn set_abandon_on_drop(&mut self, abandon: bool) {
+            self.abandon_on_drop = abandon;
+        }
+
+        /// Returns the lifecycle state of the progress bar.
+        pub fn state(&self) -> State {
+            self.state
+        }
+
+        /// Returns ```true``` if the progress bar has been finished, abandoned, or failed.
+        pub fn is_finished(&self) -> bool {
+            self.state != State::InProgress
+        }
+
+        /// Sets the percentage to 100%, prints the progress bar one last time using the finish
+        /// style, and moves the cursor to the next line.
+        pub fn finish(&mut self) {
+            if self.is_finished() {
+                return;
+            }
+            self.set_percent(1.0);
+            self.end(State::Finished, None);
+        }
+
+        /// Sets the percentage to 100%, prints the progress bar one last time using the finish
+        /// style followed by ```msg```, and moves the cursor to the next line.
+        pub fn finish_with_message(&mut self, msg: &str) {
+            if self.is_finished() {
+                return;
+            }
+            self.set_percent(1.0);
+            self.end(State::Finished, Some(msg));
+        }
+
+        /// Sets the percentage to 100% and erases the progress bar from the screen, leaving the
+        /// cursor at the beginning of the now empty line.
+        pub fn finish_and_clear(&mut self) {
+            if self.is_finished() {
+                return;
+            }
+            self.resume();
+            self.set_percent(1.0);
+            self.clear_line();
+            self.output.flush();
+            self.state = State::Finished;
+            self.drawn = false;
+            self.cursor = None;
+        }
+
+        /// Stops the progress bar at its current percentage, prints it one last time using the
+        /// abandon style, and moves the cursor to the next line.
+        pub fn abandon(&mut self) {
+            self.end(State::Abandoned, Some("(abandoned)"));
+        }
+
+        /// Stops the progress bar at its current percentage, prints it one last time using the
+        /// fail style followed by ```err```, and moves the cursor to the next line.
+        pub fn fail<E: Display>(&mut self, err: E) {
+            let msg: String = format!("\u{2718} {}", err);
+            self.end(State::Failed, Some(&msg));
+        }
+
+        /// A private function that prints the final rendering of the progress bar for ```state```,
+        /// followed by ```msg```, if any. It does nothing if the progress bar is already finished.
+        fn end(&mut self, state: State, msg: Option<&str>) {
+            if self.is_finished() {
+                return;
+            }
+            self.resume();
+            let style: text::Style = match state {
+                State::InProgress | State::Finished => self.finish_style,
+                State::Abandoned => self.abandon_style,
+                State::Failed => self.fail_style,
+            };
+            let line: String = self.final_line(style);
+            self.clear_line();
+            match msg {
+                Some(msg) => {
+                    self.output
+                        .print(&format!("{}{} {}{}\n", style, line, msg, Style::Regular))
+                }
+                None => self
+                    .output
+                    .print(&format!("{}{}{}\n", style, line, Style::Regular)),
+            }
+            self.output.flush();
+            self.state = state;
+            self.drawn = false;
+            self.cursor = None;
+        }
+
+        /// A private function that formats the progress bar for its final rendering in ```style```.
+        /// Segments with their own style switch back to the text style, so the text style is
+        /// swapped for the final rendering only. A progress bar that has ended is not stalled, so
+        /// the stall indicator is left out.
+        fn final_line(&mut self, style: text::Style) -> String {
+            let text_style: text::Style = std::mem::replace(&mut self.text_style, style);
+            let stall_threshold: Option<time::Duration> = self.stall_threshold.take();
+            let line: String = self.to_string();
+            self.text_style = text_style;
+            self.stall_threshold = stall_threshold;
+            line
+        }
+
+        /// Returns the gradient used to color the leading part of the progress bar, if any.
+        pub fn gradient(&self) -> Option<&Gradient> {
+            self.gradient.as_ref()
+        }
+
+        /// Sets the gradient used to color the leading part of the progress bar. The gradient
+        /// replaces the text::Style for the leading part only, so the label and the rest of the
+        /// bar are still printed using the text::Style. Pass ```None``` to remove the gradient.
+        pub fn set_gradient(&mut self, gradient: Option<Gradient>) {
+            self.gradient = gradient;
+        }
+
+        /// Returns the colors that the progress bar assumes the terminal is able to display.
+        pub fn color_support(&self) -> text::ColorSupport {
+            self.color_support
+        }
+
+        /// Sets the colors that the progress bar assumes the terminal is able to display. When set
+        /// to ```ColorSupport::Ansi16```, the colors of a gradient are replaced by the closest color
+        /// in the 16 color palette. The default is detected with ```text::color_support()```.
+        pub fn set_color_support(&mut self, support: text::ColorSupport) {
+            self.color_support = support;
+        }
+
+        /// Returns the length of the progress bar, excluding the label, brackets, and percentage, if any.
+        /// This is the length of the actual bar itself.
+        pub fn length(&self) -> u32 {
+            self.length
+        }
+
+        /// Sets the length of the progress bar, excluding the label, brackets, and percentage, if any.
+        /// This is the length of the actual bar itself.
+        pub fn set_length(&mut self, length: u32) {
+            self.length = length;
+        }
+
+        /// Returns the unicode character that ```self.show()``` uses in the body of the progress bar.
+        /// The leading character comprises the left side of the progress bar that represents the
+        /// portion of the task or activity that has been completed. If the leading glyph is not a
+        /// BarChar, then BarChar::FullBlock is returned, in the same way as ```BarChar::from()```.
+        /// See ```ProgressBar::leading_glyph()```.
+        pub fn leading_char(&self) -> BarChar {
+            self.leading_glyph
+                .to_bar_char()
+                .unwrap_or(BarChar::FullBlock)
+        }
+
+        /// Sets the unicode character that ```self.show()``` uses in the body of the progress bar.
+        /// The leading character comprises the left side of the progress bar that represents the
+        /// portion of the task or activity that has been completed.
+        pub fn set_leading_char(&mut self, c: BarChar) {
+            self.leading_glyph = Glyph::from(c);
+        }
+
+        /// Returns the unicode character that ```self.show()``` uses in the body of the progress bar.
+        /// The trailing character comprises the right side of the progress bar that represents the
+        /// portion of the task or activity that has not been completed. If the trailing glyph is not
+        /// a BarChar, then BarChar::FullBlock is returned, in the same way as ```BarChar::from()```.
+        /// See ```ProgressBar::trailing_glyph()```.
+        pub fn trailing_char(&self) -> BarChar {
+            self.trailing_glyph
+                .to_bar_char()
+                .unwrap_or(BarChar::FullBlock)
+        }
+
+        /// Sets the unicode character that ```self.show()``` uses in the body of the progress bar.
+        /// The trailing character comprises the right side of the progress bar that represents the
+        /// portion of the task or activity that has not been completed.
+        pub fn set_trail_char(&mut self, c: BarChar) {
+            self.trailing_glyph = Glyph::from(c);
+        }
+
+        /// Returns the glyph that is repeated to draw the leading (completed) part of the progress bar.
+        pub fn leading_glyph(&self) -> &Glyph {
+            &self.leading_glyph
+        }
+
+        /// Sets the glyph that is repeated to draw the leading (completed) part of the progress bar.
+        /// Unlike ```ProgressBar::set_leading_char()```, any printable char or grapheme can be used.
+        pub fn set_leading_glyph(&mut self, glyph: Glyph) {
+            self.leading_glyph = glyph;
+        }
+
+        /// Returns the glyph that is repeated to draw the trailing (remaining) part of the progress bar.
+        pub fn trailing_glyph(&self) -> &Glyph {
+            &self.trailing_glyph
+        }
+
+        /// Sets the glyph that is repeated to draw the trailing (remaining) part of the progress bar.
+        /// Unlike ```ProgressBar::set_trail_char()```, any printable char or grapheme can be used.
+        pub fn set_trailing_glyph(&mut self, glyph: Glyph) {
+            self.trailing_glyph = glyph;
+        }
+
+        /// Returns the glyph that is drawn between the leading and trailing parts of the progress bar, if any.
+        pub fn head_glyph(&self) -> Option<&Glyph> {
+            self.head_glyph.as_ref()
+        }
+
+        /// Sets the glyph that is drawn once between the leading and trailing parts of the progress
+        /// bar while the task is incomplete. For example, a leading glyph of "=" and a head glyph of
+        /// ">" draws an arrow style bar. Pass ```None``` to remove the head.
+        pub fn set_head_glyph(&mut self, glyph: Option<Glyph>) {
+            self.head_glyph = glyph;
+        }
+
+        /// Returns ```true``` if the progress bar is drawn in smooth mode. See ```ProgressBar::set_smooth()```.
+        pub fn smooth(&self) -> bool {
+            self.smooth
+        }
+
+        /// If ```true``` is passed, then ```self.show()``` will draw the cell at the boundary between
+        /// the leading and trailing characters using one of the unicode eighth-block characters
+        /// (▏▎▍▌▋▊▉), giving the progress bar eight times the resolution of whole cells. This
+        /// looks best when the leading character is ```BarChar::FullBlock```. The eighth blocks are
+        /// one column wide and have no head, so the progress bar falls back to whole cells while a
+        /// head glyph is set or the leading glyph is not exactly one column wide.
+        pub fn set_smooth(&mut self, smooth: bool) {
+            self.smooth = smooth;
+        }
+
+        /// If ```true``` is passed, then ```self.show()``` will print the percentage at the end of the progress bar.
+        pub fn show_percentage(&mut self, show: bool) {
+            self.show_percentage = show;
+        }
+
+        /// If ```true``` is passed, then ```self.show()``` will print the brackets at the beginning and end
+        /// of the progress bar.
+        pub fn show_brackets(&mut self, show: bool) {
+            self.show_brackets = show;
+        }
+
+        /// Returns the number of millliseconds that ```self.show()``` will sleep after printing the progress
+        /// bar to stdout. The default value is 0 milliseconds. This function is provided as a way of
+        /// "slowing down" updates to the progress bar. It is used primarily for testing or simply to
+        /// provide a better user experience.
+        pub fn interval(&self) -> time::Duration {
+            self.interval
+        }
+
+        /// Sets the number of millliseconds that ```self.show()``` will sleep before returning to its caller.
+        /// The default value is 0 milliseconds. This function is provided as a way of "slowing down"
+        /// updates to the progress bar. It is used primarily for testing or simply to provide a better
+        /// user experience.
+        pub fn set_interval(&mut self, interval: u64) {
+            self.interval = time::Duration::from_millis(interval);
+        }
+
+        /// Returns the current percentage completed of the progress bar as a number 0.0 <= n <= 1.0.
+        pub fn percent(&self) -> f32 {
+            self.percent
+        }
+
+        /// Sets the current percentage completed of the progress bar as a number 0.0 <= n <= 1.0.
+        /// Setting the same percentage again does not count as progress for the stall indicator.
+        pub fn set_percent(&mut self, percent: f32) {
+            let percent: f32 = f32::min(percent.abs(), 1.0);
+            if percent != self.percent {
+                self.percent = percent;
+                self.last_update = time::Instant::now();
+            }
+        }
+
+        /// Adds a sub-task to the progress bar and returns its id. Once a progress bar has
+        /// sub-tasks, its percentage is the weighted average of the percentages of its sub-tasks.
+        /// For example, a build with two phases could add one sub-task with a weight of 0.2 and one
+        /// with a weight of 0.8. Weights are relative to each other, so they don't need to add up
+        /// to 1.0. Use ```ProgressBar::set_child_percent()``` or a ```ChildTracker``` to report the
+        /// progress of each sub-task.
+        pub fn add_child(&mut self, label: &'static str, weight: f32) -> ChildId {
+            let id: ChildId = ChildId(self.next_child.fetch_add(1, Ordering::Relaxed));
+            self.insert_child(id, None, label, weight);
+            id
+        }
+
+        /// Adds a sub-task to the progress bar in the same way as ```ProgressBar::add_child()``` and
+        /// returns a tracker that a thread can use to report its progress through ```tx```, which
+        /// should be the sender of the channel passed to ```ProgressBar::listen()```.
+        pub fn add_child_tracker(
+            &mut self,
+            label: &'static str,
+            weight: f32,
+            tx: mpsc::Sender<Message>,
+        ) -> ChildTracker {
+            ChildTracker {
+                id: self.add_child(label, weight),
+                next_index: Arc::clone(&self.next_child),
+                tx,
+            }
+        }
+
+        /// A private function that adds the sub-task ```id```, below the sub-task ```parent``` if
+        /// there is one. Ids are handed out before their messages are sent, so sub-tasks added by
+        /// different threads may arrive out of order, leaving empty slots until they are filled.
+        /// The sub-task is ignored if its slot is already filled, if its id is more than one past
+        /// the last sub-task and was not handed out by ```self.next_child```, or if ```parent``` is
+        /// not a sub-task that was added before it. The roll-up relies on a parent having a lower
+        /// index than its sub-tasks.
+        fn insert_child(
+            &mut self,
+            id: ChildId,
+            parent: Option<ChildId>,
+            label: &'static str,
+            weight: f32,
+        ) {
+            let ChildId(index) = id;
+            let handed_out: usize = self.next_child.load(Ordering::Relaxed);
+            if index > self.children.len() && index >= handed_out {
+                return;
+            }
+            if self.child(id).is_some() {
+                return;
+            }
+            if let Some(parent) = parent {
+                if parent.0 >= index || self.child(parent).is_none() {
+                    return;
+                }
+            }
+            if index >= self.children.len() {
+                self.children.resize(index + 1, None);
+            }
+            self.children[index] = Some(Child {
+                label,
+                weight: weight.max(0.0),
+                percent: 0.0,
+                parent: parent.map(|ChildId(p)| p),
+            });
+            self.next_child.fetch_max(index + 1, Ordering::Relaxed);
+            self.update_from_children();
+        }
+
+        /// Returns the number of sub-tasks, including the sub-tasks of other sub-tasks.
+        pub fn child_count(&self) -> usize {
+            self.children.iter().flatten().count()
+        }
+
+        /// Returns the percentage completed of the sub-task ```id``` as a number 0.0 <= n <= 1.0,
+        /// or ```None``` if there is no such sub-task.
+        pub fn child_percent(&self, id: ChildId) -> Option<f32> {
+            self.child(id).map(|c| c.percent)
+        }
+
+        /// Sets the percentage completed of the sub-task ```id``` as a number 0.0 <= n <= 1.0 and
+        /// updates the percentage of the progress bar. Does nothing if there is no such sub-task.
+        /// The percentage of a sub-task that has sub-tasks of its own is always the weighted
+        /// average of theirs.
+        pub fn set_child_percent(&mut self, id: ChildId, percent: f32) {
+            if let Some(Some(child)) = self.children.get_mut(id.0) {
+                child.percent = f32::min(percent.abs(), 1.0);
+                self.update_from_children();
+            }
+        }
+
+        /// A private function that returns the sub-task ```id```, if there is one.
+        fn child(&self, id: ChildId) -> Option<&Child> {
+            self.children.get(id.0).and_then(Option::as_ref)
+        }
+
+        /// If ```true``` is passed, then ```self.show()``` will print each sub-task as its own indented
+        /// progress bar on the lines below the progress bar.
+        pub fn show_children(&mut self, show: bool) {
+            self.show_children = show;
+        }
+
+        /// A private function that sets the percentage of each sub-task that has sub-tasks, and of
+        /// the progress bar, to the weighted average of the percentages of its sub-tasks. A
+        /// sub-task always has a higher index than its parent, so walking the sub-tasks backwards
+        /// finishes each one before it is added to its parent.
+        fn update_from_children(&mut self) {
+            let n: usize = self.children.len();
+            let mut totals: Vec<(f32, f32)> = vec![(0.0, 0.0); n];
+            let mut root: (f32, f32) = (0.0, 0.0);
+            for i in (0..n).rev() {
+                let Some(child) = self.children[i].as_mut() else {
+                    continue;
+                };
+                let (total, done): (f32, f32) = totals[i];
+                if total > 0.0 {
+                    child.percent = done / total;
+                }
+                let sums: &mut (f32, f32) = match child.parent {
+                    Some(parent) if parent < i => &mut totals[parent],
+                    Some(_) => continue,
+                    None => &mut root,
+                };
+                sums.0 += child.weight;
+                sums.1 += child.weight * child.percent;
+            }
+            if root.0 > 0.0 {
+                self.set_percent(root.1 / root.0);
+            }
+        }
+
+        /// A private function that formats the sub-tasks below ```parent```, and their sub-tasks,
+        /// as progress bars indented by ```depth```.
+        fn child_lines(&self, parent: Option<usize>, depth: usize, lines: &mut Vec<String>) {
+            for (i, child) in self.children.iter().enumerate() {
+                if let Some(child) = child.as_ref().filter(|c| c.parent == parent) {
+                    lines.push(self.child_line(child, depth));
+                    self.child_lines(Some(i), depth + 1, lines);
+                }
+            }
+        }
+
+        /// A private function that formats the sub-task ```child``` as a progress bar indented by
+        /// ```depth```.
+        fn child_line(&self, child: &Child, depth: usize) -> String {
+            let mut s: String = "  ".repeat(depth + 1);
+            self.write_styled(&mut s, self.label_style, child.label);
+            if self.show_brackets {
+                self.write_styled(&mut s, self.bracket_style, "[");
+            }
+            self.write_bar(&mut s, child.percent);
+            if self.show_brackets {
+                self.write_styled(&mut s, self.bracket_style, "]");
+            }
+            if self.show_percentage {
+                s.push(' ');
+                let percentage: String = format!("{:.1}%", child.percent * 100.0);
+                self.write_styled(&mut s, self.percent_style, &percentage);
+            }
+            s
+        }
+
+        /// Returns the interval at which ```ProgressBar::listen()``` redraws the progress bar when no
+        /// messages are received, if any.
+        pub fn steady_tick(&self) -> Option<time::Duration> {
+            self.steady_tick
+        }
+
+        /// Sets the number of milliseconds that ```ProgressBar::listen()``` will wait for a message
+        /// before redrawing the progress bar anyway. This keeps the elapsed time and the stall
+        /// indicator up to date while the task is not making progress. Pass ```None``` to only
+        /// redraw when a message is received.
+        pub fn set_steady_tick(&mut self, interval: Option<u64>) {
+            self.steady_tick = interval.map(time::Duration::from_millis);
+        }
+
+        /// Returns how long the percentage must stay the same before the progress bar is shown as stalled, if ever.
+        pub fn stall_threshold(&self) -> Option<time::Duration> {
+            self.stall_threshold
+        }
+
+        /// Sets the number of milliseconds that the percentage must stay the same before the
+        /// progress bar shows how long it has been stalled for, e.g. "stalled for 30s". This is
+        /// best combined with ```ProgressBar::set_steady_tick()```. Pass ```None``` to disable it.
+        pub fn set_stall_threshold(&mut self, threshold: Option<u64>) {
+            self.stall_threshold = threshold.map(time::Duration::from_millis);
+        }
+
+        /// If ```true``` is passed, then ```self.show()``` will print the elapsed time after the percentage.
+        pub fn show_elapsed(&mut self, show: bool) {
+            self.show_elapsed = show;
+        }
+
+        /// If ```true``` is passed, then ```self.show()``` will print the estimated time remaining after the
+        /// percentage.
+        pub fn show_eta(&mut self, show: bool) {
+            self.show_eta = show;
+        }
+
+        /// Returns the time elapsed since the progress bar was created, excluding any time spent paused.
+        pub fn elapsed(&self) -> time::Duration {
+            let paused: time::Duration = match self.paused_at {
+                Some(t) => self.paused_total + t.elapsed(),
+                None => self.paused_total,
+            };
+            self.started.elapsed().saturating_sub(paused)
+        }
+
+        /// Returns ```true``` if the progress bar is paused.
+        pub fn is_paused(&self) -> bool {
+            self.paused_at.is_some()
+        }
+
+        /// Pauses the progress bar. Until ```ProgressBar::resume()``` is called, time does not count
+        /// towards the elapsed time, the estimated time remaining, or the stall indicator, and the
+        /// progress bar shows that it is paused. This is useful while waiting on user input or a
+        /// rate limiter.
+        pub fn pause(&mut self) {
+            if self.paused_at.is_none() {
+                self.paused_at = Some(time::Instant::now());
+            }
+        }
+
+        /// Resumes a paused progress bar. See ```ProgressBar::pause()```.
+        pub fn resume(&mut self) {
+            if let Some(t) = self.paused_at.take() {
+                let paused: time::Duration = t.elapsed();
+                self.paused_total += paused;
+                self.last_update += paused;
+            }
+        }
+
+        /// Returns the estimated time remaining based on the elapsed time and the percentage
+        /// completed so far, or ```None``` if no progress has been made yet.
+        pub fn eta(&self) -> Option<time::Duration> {
+            if self.percent <= 0.0 {
+                return None;
+            }
+            let elapsed: f64 = self.elapsed().as_secs_f64();
+            let remaining: f64 = elapsed * (1.0 - self.percent as f64) / self.percent as f64;
+            Some(time::Duration::from_secs_f64(remaining))
+        }
+
+        /// A private function that writes the elapsed time, estimated time remaining and stall
+        /// indicator to ```s```, depending on the configuration of the progress bar.
+        fn write_stats(&self, s: &mut String) {
+            use std::fmt::Write;
+            if self.show_elapsed {
+                write!(s, " {:.0}", HumanDuration(self.elapsed())).unwrap();
+            }
+            if self.show_eta && self.percent < 1.0 {
+                match self.eta() {
+                    Some(eta) => write!(s, " ETA {:.0}", HumanDuration(eta)).unwrap(),
+                    None => s.push_str(" ETA -"),
+                }
+            }
+            if self.is_paused() {
+                s.push_str(" (paused)");
+                return;
+            }
+            if let Some(threshold) = self.stall_threshold {
+                let stalled: time::Duration = self.last_update.elapsed();
+                if stalled >= threshold && self.percent < 1.0 {
+                    write!(s, " (stalled for {:.0})", HumanDuration(stalled)).unwrap();
+                }
+            }
+        }
+
+        /// Returns the text label to the left of the progress bar
+        pub fn label(&self) -> &'static str {
+            self.label
+        }
+
+        /// Sets the text label to the left of the progress bar
+        pub fn set_label(&mut self, msg: &'static str) {
+            self.label = msg;
+        }
+
+        /// A private function that writes the body of the progress bar, excluding the label,
+        /// brackets and percentage, to ```s```. The length of the bar is measured in terminal
+        /// columns, so glyphs that are more than one column wide are repeated fewer times and any
+        /// columns that cannot fit a whole glyph are padded with spaces.
+        fn write_bar(&self, s: &mut String, percent: f32) {
+            let length: usize = self.length as usize;
+            let mut filled: usize = (length as f32 * percent).round() as usize;
+            let mut partial: Option<BarChar> = None;
+            if self.smooth && self.head_glyph.is_none() && self.leading_glyph.width() == 1 {
+                let eighths: usize = (length as f32 * percent * 8.0).floor() as usize;
+                filled = usize::min(eighths / 8, length);
+                let remainder: usize = eighths % 8;
+                if filled < length && remainder > 0 {
+                    partial = Some(PARTIAL_BLOCKS[remainder - 1]);
+                }
+            }
+            let mut columns: usize = 0;
+            let mut color: Option<text::Color> = None;
+            if let Some(style) = self.leading_style {
+                s.push_str(&style.to_string());
+            }
+            while columns + self.leading_glyph.width() <= filled {
+                self.write_gradient_color(s, columns, percent, &mut color);
+                s.push_str(self.leading_glyph.as_str());
+                columns += self.leading_glyph.width();
+            }
+            if let Some(c) = partial {
+                self.write_gradient_color(s, columns, percent, &mut color);
+                s.push(c.to_char());
+                columns += 1;
+            } else if let Some(head) = &self.head_glyph {
+                if filled < length && columns + head.width() <= length {
+                    self.write_gradient_color(s, columns, percent, &mut color);
+                    s.push_str(head.as_str());
+                    columns += head.width();
+                }
+            }
+            if color.is_some() || self.leading_style.is_some() {
+                // switch back to the text style for the trailing part of the bar
+                self.restore_style(s);
+            }
+            if let Some(style) = self.trailing_style {
+                s.push_str(&style.to_string());
+            }
+            while columns + self.trailing_glyph.width() <= length {
+                s.push_str(self.trailing_glyph.as_str());
+                columns += self.trailing_glyph.width();
+            }
+            for _ in columns..length {
+                s.push(' ');
+            }
+            if self.trailing_style.is_some() {
+                self.restore_style(s);
+            }
+        }
+
+        /// A private function that writes ```segment``` to ```s``` using ```style```, if any, and then
+        /// switches back to the text style.
+        fn write_styled(&self, s: &mut String, style: Option<text::Style>, segment: &str) {
+            match style {
+                Some(style) => {
+                    s.push_str(&style.to_string());
+                    s.push_str(segment);
+                    self.restore_style(s);
+                }
+                None => s.push_str(segment),
+            }
+        }
+
+        /// A private function that resets all text attributes and re-applies the text style.
+        fn restore_style(&self, s: &mut String) {
+            use std::fmt::Write;
+            write!(s, "{}{}", Style::Regular, self.text_style).unwrap();
+        }
+
+        /// A private function that writes the escape sequence for the gradient color of the cell
+        /// at ```column``` of a bar that is ```percent``` complete to ```s```, unless it is the same
+        /// as the ```current``` color.
+        fn write_gradient_color(
+            &self,
+            s: &mut String,
+            column: usize,
+            percent: f32,
+            current: &mut Option<text::Color>,
+        ) {
+            let gradient: &Gradient = match &self.gradient {
+                Some(g) => g,
+                None => return,
+            };
+            let t: f32 = match gradient.mode() {
+                GradientMode::Percent => percent,
+                GradientMode::Horizontal if self.length > 1 => {
+                    column as f32 / (self.length - 1) as f32
+                }
+                GradientMode::Horizontal => 0.0,
+            };
+            let color: Option<text::Color> = gradient
+                .color_at(t)
+                .map(|c| c.for_support(self.color_support));
+            if let Some(c) = color.filter(|c| Some(*c) != *current) {
+                use std::fmt::Write;
+                write!(s, "{}", c).unwrap();
+                *current = color;
+            }
+        }
+
+        /// A private function that clears the previous progress bar printed to stdout. This function is
+        /// called by ```ProgressBar::show()``` immediately prior to printing the progress bar to stdout.
+        /// If sub-tasks were printed below the progress bar, then their lines are cleared as well and
+        /// the cursor is left at the beginning of the line of the progress bar itself. See
+        /// ```ProgressBar::draw()``` for more information.
+        fn clear_line(&self) {
+            for i in 0..=self.prev_lines {
+                self.output.print(&format!("\r{}", Erase::Line));
+                if i < self.prev_lines {
+                    self.output.print(&Cursor::Up(1).to_string());
+                }
+            }
+        }
+
+        /// Listens for messages on ```rx```, executes the message and calls ```self.show()```.
+        ///
+        /// ```
+        /// use cli_tools::pbar::{Message, ProgressBar};
+        /// use std::{sync::mpsc, thread};
+        ///
+        /// fn myfunc() {
         ///     let mut pbar: ProgressBar = ProgressBar::default();
         ///
         ///     // establish a channel that our thread can use to send messages
@@ -438,18 +1984,37 @@ pub mod pbar {
         ///             }
         ///             if n % 1000 == 0 {
         ///                 // don't need to update the progress bar each time
-        ///                 tx.send(Progress::from(n as f32 / 1000000.0_f32)).unwrap();
+        ///                 tx.send(Message::Percent(n as f32 / 1000000.0_f32)).unwrap();
         ///             }
         ///         }
         ///     });
         ///
-        ///     pbar.show_thread(&rx);
+        ///     pbar.listen(&rx);
+        ///     pbar.finish();
         ///
-        ///     println!("\nDone working!");
+        ///     println!("Done working!");
         /// }
         /// ```
+        ///
+        /// The function returns when the channel is closed or after a message that finishes
+        /// the progress bar, such as ```Message::Finish```, has been executed. If a steady tick is
+        /// set, then the progress bar is also redrawn whenever no message arrives in time.
         pub fn listen(&mut self, rx: &mpsc::Receiver<Message>) {
-            for msg in rx {
+            loop {
+                let msg: Message = match self.steady_tick {
+                    Some(tick) => match rx.recv_timeout(tick) {
+                        Ok(msg) => msg,
+                        Err(mpsc::RecvTimeoutError::Timeout) => {
+                            self.draw();
+                            continue;
+                        }
+                        Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                    },
+                    None => match rx.recv() {
+                        Ok(msg) => msg,
+                        Err(_) => return,
+                    },
+                };
                 match msg {
                     Message::LeadingChar(c) => self.set_leading_char(c),
                     Message::TrailingChar(c) => self.set_trail_char(c),
@@ -460,6 +2025,41 @@ pub mod pbar {
                     Message::ShowPercentage(show) => self.show_percentage(show),
                     Message::ShowBrackets(show) => self.show_brackets(show),
                     Message::TextStyle(style) => self.set_style(style),
+                    Message::Smooth(smooth) => self.set_smooth(smooth),
+                    Message::LeadingGlyph(g) => self.set_leading_glyph(g),
+                    Message::TrailingGlyph(g) => self.set_trailing_glyph(g),
+                    Message::HeadGlyph(g) => self.set_head_glyph(g),
+                    Message::Gradient(g) => self.set_gradient(g),
+                    Message::LabelStyle(style) => self.set_label_style(style),
+                    Message::LeadingStyle(style) => self.set_leading_style(style),
+                    Message::TrailingStyle(style) => self.set_trailing_style(style),
+                    Message::BracketStyle(style) => self.set_bracket_style(style),
+                    Message::PercentStyle(style) => self.set_percent_style(style),
+                    Message::Finish => self.finish(),
+                    Message::FinishWithMessage(msg) => self.finish_with_message(&msg),
+                    Message::FinishAndClear => self.finish_and_clear(),
+                    Message::Abandon => self.abandon(),
+                    Message::Fail(err) => self.fail(err),
+                    Message::AbandonOnDrop(abandon) => self.set_abandon_on_drop(abandon),
+                    Message::Print(line) => {
+                        // println() redraws the progress bar without sleeping
+                        self.println(&line);
+                        continue;
+                    }
+                    Message::SteadyTick(tick) => self.set_steady_tick(tick),
+                    Message::StallThreshold(threshold) => self.set_stall_threshold(threshold),
+                    Message::ShowElapsed(show) => self.show_elapsed(show),
+                    Message::ShowEta(show) => self.show_eta(show),
+                    Message::Pause => self.pause(),
+                    Message::Resume => self.resume(),
+                    Message::AddChild(id, parent, label, weight) => {
+                        self.insert_child(id, parent, label, weight)
+                    }
+                    Message::ChildPercent(id, percent) => self.set_child_percent(id, percent),
+                    Message::ShowChildren(show) => self.show_children(show),
+                }
+                if self.is_finished() {
+                    return;
                 }
                 self.show();
             }
@@ -469,13 +2069,7358 @@ pub mod pbar {
         /// of the line, and prints the progress bar. If ```self.interval``` is set to a non-zero
         /// value, then it will sleep for as many milliseconds before returning to the caller. This
         /// is intended to give the caller the ability to slow down the loop for presentation
-        /// purposes if desired.
+        /// purposes if desired. Nothing is printed once the progress bar is finished.
         pub fn show(&mut self) {
-            self.clear_line();
-            print!("{}{}{}", self.text_style, self, Style::Regular);
-            self.save_line_length();
-            std::io::stdout().flush().unwrap();
+            if self.is_finished() {
+                return;
+            }
+            self.draw();
             thread::sleep(self.interval);
         }
+
+        /// A private function that formats the progress bar and, if they are shown, its sub-tasks.
+        fn lines(&self) -> Vec<String> {
+            let mut lines: Vec<String> = vec![self.to_string()];
+            if self.show_children {
+                self.child_lines(None, 0, &mut lines);
+            }
+            lines
+        }
+
+        /// A private function that formats the progress bar and its sub-tasks in the same way as
+        /// ```ProgressBar::lines()```, but shortens the bar so that each line is narrower than
+        /// ```cols``` columns. Lines that are still too wide once the bar is gone are truncated.
+        fn fit_lines(&mut self, cols: usize) -> Vec<String> {
+            let lines: Vec<String> = self.lines();
+            let widest: usize = lines
+                .iter()
+                .map(|l| text::visible_width(l))
+                .max()
+                .unwrap_or(0);
+            if widest < cols {
+                return lines;
+            }
+            let length: u32 = self.length;
+            self.length = length.saturating_sub((widest - cols + 1) as u32);
+            let lines: Vec<String> = self.lines();
+            self.length = length;
+            lines
+                .iter()
+                .map(|line| text::truncate(line, cols.saturating_sub(1)))
+                .collect()
+        }
+
+        /// A private function that clears the current line and prints the progress bar. If the
+        /// terminal is too narrow, the bar is shortened so that each line fits. The cursor
+        /// is hidden the first time the progress bar is printed and shown again once it is
+        /// finished or dropped, so it does not flicker at the end of the bar. The number
+        /// of lines printed for the sub-tasks is saved so that ```ProgressBar::clear_line()``` can
+        /// erase them before the next time the progress bar is printed.
+        fn draw(&mut self) {
+            if self.cursor.is_none() {
+                self.cursor = Some(CursorGuard::hide());
+            }
+            self.clear_line();
+            // no line may wrap, which would break clear_line()
+            let lines: Vec<String> = match term::size() {
+                Some(size) => self.fit_lines(size.cols as usize),
+                None => self.lines(),
+            };
+            for (i, line) in lines.iter().enumerate() {
+                if i > 0 {
+                    self.output.print("\n");
+                }
+                self.output
+                    .print(&format!("{}{}{}", self.text_style, line, Style::Regular));
+            }
+            self.prev_lines = lines.len() - 1;
+            self.drawn = true;
+            self.output.flush();
+        }
+
+        /// Prints ```line``` followed by a newline above the progress bar. Use this instead of
+        /// ```println!()``` while the progress bar is on the screen, otherwise the line will be
+        /// printed over the top of the progress bar. See ```ProgressBar::suspend()```.
+        pub fn println(&mut self, line: &str) {
+            let output: Output = self.output.clone();
+            self.suspend(|| output.print(&format!("{}\n", line)));
+        }
+
+        /// Erases the progress bar from the screen, calls ```f```, and then prints the progress bar
+        /// again below whatever ```f``` printed. Returns the value returned by ```f```. Any output
+        /// printed by ```f``` should end with a newline.
+        pub fn suspend<F: FnOnce() -> R, R>(&mut self, f: F) -> R {
+            let visible: bool = self.drawn && !self.is_finished();
+            if visible {
+                self.clear_line();
+                self.output.flush();
+            }
+            let result: R = f();
+            if visible {
+                // the cursor is already at the start of an empty line
+                self.prev_lines = 0;
+                self.draw();
+            }
+            result
+        }
+    }
+
+    impl Clone for ProgressBar {
+        /// Creates a copy of the progress bar with the same configuration, percentage and
+        /// sub-tasks. The copy has not been printed yet, so it can be shown on its own.
+        /// ```ChildTracker```s created from the original keep reporting to the original.
+        fn clone(&self) -> Self {
+            Self {
+                length: self.length,
+                leading_glyph: self.leading_glyph.clone(),
+                trailing_glyph: self.trailing_glyph.clone(),
+                head_glyph: self.head_glyph.clone(),
+                show_percentage: self.show_percentage,
+                show_brackets: self.show_brackets,
+                interval: self.interval,
+                percent: self.percent,
+                label: self.label,
+                text_style: self.text_style,
+                smooth: self.smooth,
+                gradient: self.gradient.clone(),
+                color_support: self.color_support,
+                label_style: self.label_style,
+                leading_style: self.leading_style,
+                trailing_style: self.trailing_style,
+                bracket_style: self.bracket_style,
+                percent_style: self.percent_style,
+                state: self.state,
+                drawn: false,
+                finish_style: self.finish_style,
+                abandon_style: self.abandon_style,
+                fail_style: self.fail_style,
+                abandon_on_drop: self.abandon_on_drop,
+                steady_tick: self.steady_tick,
+                stall_threshold: self.stall_threshold,
+                show_elapsed: self.show_elapsed,
+                show_eta: self.show_eta,
+                started: self.started,
+                last_update: self.last_update,
+                paused_at: self.paused_at,
+                paused_total: self.paused_total,
+                children: self.children.clone(),
+                next_child: Arc::new(AtomicUsize::new(self.next_child.load(Ordering::Relaxed))),
+                show_children: self.show_children,
+                prev_lines: 0,
+                cursor: None,
+                output: self.output.clone(),
+            }
+        }
+    }
+
+    impl Drop for ProgressBar {
+        /// Erases the progress bar, or abandons it if abandon on drop is turned on, if it has been
+        /// printed but not finished.
+        fn drop(&mut self) {
+            if !self.drawn || self.is_finished() {
+                return;
+            }
+            if self.abandon_on_drop {
+                self.abandon();
+            } else {
+                self.clear_line();
+                self.output.flush();
+            }
+        }
+    }
+
+    /// A cloneable handle that counts completed units of work, such as items or bytes, and reports
+    /// the percentage to a ProgressBar through a ```mpsc::channel```. Sending on a channel never
+    /// blocks, so a handle can be used from async tasks and from threads alike. A message is only
+    /// sent when the percentage rises by at least 0.1% to avoid flooding the channel, so the
+    /// percentages received from a handle and its clones never decrease.
+    #[derive(Debug, Clone)]
+    pub struct ProgressHandle {
+        tx: mpsc::Sender<Message>,
+        position: Arc<AtomicU64>,
+        total: Arc<AtomicU64>,
+        last_permille: Arc<Mutex<Option<u64>>>,
+    }
+
+    impl ProgressHandle {
+        /// Creates a new handle for a task with ```total``` units of work that sends its updates through ```tx```.
+        pub fn new(tx: mpsc::Sender<Message>, total: u64) -> Self {
+            Self {
+                tx,
+                position: Arc::new(AtomicU64::new(0)),
+                total: Arc::new(AtomicU64::new(total)),
+                last_permille: Arc::new(Mutex::new(None)),
+            }
+        }
+
+        /// Returns the number of units of work completed so far.
+        pub fn position(&self) -> u64 {
+            self.position.load(Ordering::Relaxed)
+        }
+
+        /// Returns the total number of units of work.
+        pub fn total(&self) -> u64 {
+            self.total.load(Ordering::Relaxed)
+        }
+
+        /// Sets the total number of units of work, e.g. once the length of a download is known.
+        pub fn set_total(&self, total: u64) {
+            self.total.store(total, Ordering::Relaxed);
+            self.report();
+        }
+
+        /// Adds ```delta``` to the number of units of work completed so far.
+        pub fn inc(&self, delta: u64) {
+            self.position.fetch_add(delta, Ordering::Relaxed);
+            self.report();
+        }
+
+        /// Sets the number of units of work completed so far.
+        pub fn set_position(&self, position: u64) {
+            self.position.store(position, Ordering::Relaxed);
+            self.report();
+        }
+
+        /// Sends ```msg``` to the progress bar. Returns ```false``` if the progress bar is no longer listening.
+        pub fn send(&self, msg: Message) -> bool {
+            self.tx.send(msg).is_ok()
+        }
+
+        /// A private function that sends the current percentage to the progress bar if it has
+        /// risen by at least 0.1% since it was last sent. The lock is held while sending, so clones
+        /// on different threads cannot send their percentages out of order. Errors are ignored
+        /// because a progress bar that stopped listening should not interrupt the work being tracked.
+        fn report(&self) {
+            let total: u64 = self.total();
+            if total == 0 {
+                return;
+            }
+            let mut last: std::sync::MutexGuard<Option<u64>> =
+                self.last_permille.lock().unwrap_or_else(|e| e.into_inner());
+            let percent: f32 = (self.position() as f64 / total as f64).min(1.0) as f32;
+            let permille: u64 = (percent * 1000.0) as u64;
+            // None is less than any Some, so the first percentage is always sent
+            if *last < Some(permille) {
+                *last = Some(permille);
+                let _ = self.tx.send(Message::Percent(percent));
+            }
+        }
+    }
+
+    /// A module for reporting progress from async code without depending on any particular
+    /// async runtime. It defines minimal ```Stream``` and ```AsyncRead``` traits, adapters that
+    /// advance a ```ProgressHandle``` as items or bytes pass through them, and ```block_on()```,
+    /// a minimal executor that is enough to drive them in tests and simple programs.
+    ///
+    /// ```
+    /// use cli_tools::pbar::aio::{self, ProgressStream};
+    /// use cli_tools::pbar::{Message, ProgressHandle};
+    /// use std::sync::mpsc;
+    ///
+    /// let (tx, rx) = mpsc::channel::<Message>();
+    /// let handle: ProgressHandle = ProgressHandle::new(tx, 4);
+    /// let mut items = ProgressStream::new(aio::iter(vec![1, 2, 3, 4]), handle.clone());
+    ///
+    /// let sum: i32 = aio::block_on(async {
+    ///     let mut sum: i32 = 0;
+    ///     while let Some(n) = aio::next(&mut items).await {
+    ///         sum += n;
+    ///     }
+    ///     sum
+    /// });
+    ///
+    /// assert_eq!(sum, 10);
+    /// assert_eq!(handle.position(), 4);
+    /// assert!(rx.try_iter().count() > 0);
+    /// ```
+    pub mod aio {
+
+        use super::ProgressHandle;
+        use std::future::Future;
+        use std::io;
+        use std::pin::Pin;
+        use std::sync::Arc;
+        use std::task::{Context, Poll, Wake, Waker};
+        use std::thread;
+
+        /// An asynchronous sequence of items. This has the same shape as the ```Stream``` trait of
+        /// the ```futures``` crate, so adapting a stream from any runtime is a one line
+        /// ```poll_next()``` forward.
+        pub trait Stream {
+            type Item;
+
+            /// Attempts to pull out the next item of the stream. Returns ```Poll::Ready(None)```
+            /// once the stream is exhausted.
+            fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>>;
+        }
+
+        /// An asynchronous source of bytes. This has the same shape as the ```AsyncRead``` trait
+        /// of the ```futures``` crate.
+        pub trait AsyncRead {
+            /// Attempts to read bytes into ```buf```. Returns the number of bytes read, which is
+            /// zero at the end of the source.
+            fn poll_read(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut [u8],
+            ) -> Poll<io::Result<usize>>;
+        }
+
+        /// A stream adapter that increments a ```ProgressHandle``` by one for every item it yields.
+        #[derive(Debug)]
+        pub struct ProgressStream<S> {
+            inner: S,
+            handle: ProgressHandle,
+        }
+
+        impl<S> ProgressStream<S> {
+            /// Wraps ```inner``` so that every item it yields increments ```handle```.
+            pub fn new(inner: S, handle: ProgressHandle) -> Self {
+                Self { inner, handle }
+            }
+
+            /// Returns the handle that is incremented for every item.
+            pub fn handle(&self) -> &ProgressHandle {
+                &self.handle
+            }
+
+            /// Returns the wrapped stream.
+            pub fn into_inner(self) -> S {
+                self.inner
+            }
+        }
+
+        impl<S: Stream + Unpin> Stream for ProgressStream<S> {
+            type Item = S::Item;
+
+            fn poll_next(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+            ) -> Poll<Option<Self::Item>> {
+                let poll = Pin::new(&mut self.inner).poll_next(cx);
+                if let Poll::Ready(Some(_)) = poll {
+                    self.handle.inc(1);
+                }
+                poll
+            }
+        }
+
+        /// A reader adapter that increments a ```ProgressHandle``` by the number of bytes read.
+        #[derive(Debug)]
+        pub struct ProgressReader<R> {
+            inner: R,
+            handle: ProgressHandle,
+        }
+
+        impl<R> ProgressReader<R> {
+            /// Wraps ```inner``` so that every byte read from it increments ```handle```.
+            pub fn new(inner: R, handle: ProgressHandle) -> Self {
+                Self { inner, handle }
+            }
+
+            /// Returns the handle that is incremented for every byte.
+            pub fn handle(&self) -> &ProgressHandle {
+                &self.handle
+            }
+
+            /// Returns the wrapped reader.
+            pub fn into_inner(self) -> R {
+                self.inner
+            }
+        }
+
+        impl<R: AsyncRead + Unpin> AsyncRead for ProgressReader<R> {
+            fn poll_read(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut [u8],
+            ) -> Poll<io::Result<usize>> {
+                let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+                if let Poll::Ready(Ok(n)) = poll {
+                    self.handle.inc(n as u64);
+                }
+                poll
+            }
+        }
+
+        /// A stream that yields the items of an iterator. It is always ready.
+        #[derive(Debug)]
+        pub struct Iter<I> {
+            iter: I,
+        }
+
+        /// Creates a stream that yields the items of ```iter```.
+        pub fn iter<I: IntoIterator>(iter: I) -> Iter<I::IntoIter> {
+            Iter {
+                iter: iter.into_iter(),
+            }
+        }
+
+        impl<I: Iterator + Unpin> Stream for Iter<I> {
+            type Item = I::Item;
+
+            fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<I::Item>> {
+                Poll::Ready(self.iter.next())
+            }
+        }
+
+        impl<T: AsRef<[u8]> + Unpin> AsyncRead for io::Cursor<T> {
+            fn poll_read(
+                self: Pin<&mut Self>,
+                _: &mut Context<'_>,
+                buf: &mut [u8],
+            ) -> Poll<io::Result<usize>> {
+                Poll::Ready(io::Read::read(self.get_mut(), buf))
+            }
+        }
+
+        /// The future returned by ```next()```.
+        #[derive(Debug)]
+        pub struct Next<'a, S> {
+            stream: &'a mut S,
+        }
+
+        /// Returns a future that resolves to the next item of ```stream```, or ```None``` once it is exhausted.
+        pub fn next<S: Stream + Unpin>(stream: &mut S) -> Next<'_, S> {
+            Next { stream }
+        }
+
+        impl<S: Stream + Unpin> Future for Next<'_, S> {
+            type Output = Option<S::Item>;
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+                Pin::new(&mut *self.stream).poll_next(cx)
+            }
+        }
+
+        /// The future returned by ```read()```.
+        #[derive(Debug)]
+        pub struct Read<'a, R> {
+            reader: &'a mut R,
+            buf: &'a mut [u8],
+        }
+
+        /// Returns a future that reads bytes from ```reader``` into ```buf``` and resolves to the number of bytes read.
+        pub fn read<'a, R: AsyncRead + Unpin>(reader: &'a mut R, buf: &'a mut [u8]) -> Read<'a, R> {
+            Read { reader, buf }
+        }
+
+        impl<R: AsyncRead + Unpin> Future for Read<'_, R> {
+            type Output = io::Result<usize>;
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+                let this = &mut *self;
+                Pin::new(&mut *this.reader).poll_read(cx, this.buf)
+            }
+        }
+
+        /// A private waker that unparks the thread that is blocked in ```block_on()```.
+        struct ThreadWaker(thread::Thread);
+
+        impl Wake for ThreadWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        /// Runs ```future``` to completion on the current thread and returns its output. The thread
+        /// is parked whenever the future is pending and unparked when it is woken.
+        pub fn block_on<F: Future>(future: F) -> F::Output {
+            let mut future = std::pin::pin!(future);
+            let waker: Waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+            let mut cx: Context<'_> = Context::from_waker(&waker);
+            loop {
+                if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                    return output;
+                }
+                thread::park();
+            }
+        }
+
+        #[cfg(test)]
+        mod tests {
+
+            use super::*;
+            use crate::pbar::{Message, ProgressHandle};
+            use std::sync::mpsc;
+
+            /// A future that is pending the first time it is polled and wakes itself from another
+            /// thread, so that ```block_on()``` has to park and be unparked.
+            struct WakeLater {
+                polled: bool,
+            }
+
+            impl Future for WakeLater {
+                type Output = u32;
+
+                fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<u32> {
+                    if self.polled {
+                        return Poll::Ready(7);
+                    }
+                    self.polled = true;
+                    let waker: Waker = cx.waker().clone();
+                    thread::spawn(move || {
+                        thread::sleep(std::time::Duration::from_millis(10));
+                        waker.wake();
+                    });
+                    Poll::Pending
+                }
+            }
+
+            fn percents(rx: &mpsc::Receiver<Message>) -> Vec<f32> {
+                rx.try_iter()
+                    .map(|msg| match msg {
+                        Message::Percent(p) => p,
+                        msg => panic!("unexpected message {:?}", msg),
+                    })
+                    .collect()
+            }
+
+            #[test]
+            fn handle_sends_only_when_the_permille_changes() {
+                let (tx, rx) = mpsc::channel::<Message>();
+                let handle: ProgressHandle = ProgressHandle::new(tx, 100_000);
+                for _ in 0..100_000 {
+                    handle.inc(1);
+                }
+                let sent: Vec<f32> = percents(&rx);
+                assert!(sent.len() <= 1001, "{} messages", sent.len());
+                assert!(sent.windows(2).all(|w| w[0] < w[1]));
+                assert_eq!(sent.last(), Some(&1.0));
+                handle.set_position(100_000);
+                assert!(percents(&rx).is_empty());
+            }
+
+            #[test]
+            fn handle_clones_never_send_a_lower_percentage() {
+                let (tx, rx) = mpsc::channel::<Message>();
+                let handle: ProgressHandle = ProgressHandle::new(tx, 400_000);
+                let workers: Vec<thread::JoinHandle<()>> = (0..4)
+                    .map(|_| {
+                        let handle: ProgressHandle = handle.clone();
+                        thread::spawn(move || {
+                            for _ in 0..100_000 {
+                                handle.inc(1);
+                            }
+                        })
+                    })
+                    .collect();
+                for worker in workers {
+                    worker.join().unwrap();
+                }
+                let sent: Vec<f32> = percents(&rx);
+                assert!(sent.windows(2).all(|w| w[0] < w[1]));
+                assert_eq!(sent.last(), Some(&1.0));
+                handle.set_position(0);
+                assert!(percents(&rx).is_empty());
+            }
+
+            #[test]
+            fn handle_without_a_total_sends_nothing() {
+                let (tx, rx) = mpsc::channel::<Message>();
+                let handle: ProgressHandle = ProgressHandle::new(tx, 0);
+                handle.inc(5);
+                assert!(percents(&rx).is_empty());
+                handle.set_total(10);
+                assert_eq!(percents(&rx), [0.5]);
+                handle.inc(20);
+                assert_eq!(percents(&rx), [1.0]);
+            }
+
+            #[test]
+            fn reader_counts_the_bytes_read() {
+                let (tx, rx) = mpsc::channel::<Message>();
+                let handle: ProgressHandle = ProgressHandle::new(tx, 10);
+                let mut reader = ProgressReader::new(io::Cursor::new(vec![1u8; 10]), handle);
+                let mut buf: [u8; 4] = [0; 4];
+                let counts: Vec<usize> = block_on(async {
+                    let mut counts: Vec<usize> = Vec::new();
+                    loop {
+                        let n: usize = read(&mut reader, &mut buf).await.unwrap();
+                        counts.push(n);
+                        if n == 0 {
+                            return counts;
+                        }
+                    }
+                });
+                assert_eq!(counts, [4, 4, 2, 0]);
+                assert_eq!(reader.handle().position(), 10);
+                assert_eq!(percents(&rx), [0.4, 0.8, 1.0]);
+            }
+
+            #[test]
+            fn stream_counts_the_items_yielded() {
+                let (tx, _rx) = mpsc::channel::<Message>();
+                let handle: ProgressHandle = ProgressHandle::new(tx, 3);
+                let mut items = ProgressStream::new(iter(["a", "b", "c"]), handle);
+                assert_eq!(block_on(next(&mut items)), Some("a"));
+                assert_eq!(items.handle().position(), 1);
+                while block_on(next(&mut items)).is_some() {}
+                assert_eq!(items.handle().position(), 3);
+            }
+
+            #[test]
+            fn block_on_parks_until_the_future_is_woken() {
+                assert_eq!(block_on(WakeLater { polled: false }), 7);
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+
+        use super::*;
+
+        fn close(a: f32, b: f32) -> bool {
+            (a - b).abs() < 1e-6
+        }
+
+        fn bar(bar: &ProgressBar, percent: f32) -> String {
+            let mut s: String = String::new();
+            bar.write_bar(&mut s, percent);
+            s
+        }
+
+        #[test]
+        fn smooth_bars_draw_partial_cells() {
+            let mut progress: ProgressBar = ProgressBar::new("");
+            progress.set_length(10);
+            assert_eq!(bar(&progress, 0.55), "██████░░░░");
+            progress.set_smooth(true);
+            assert_eq!(bar(&progress, 0.55), "█████▌░░░░");
+            assert_eq!(bar(&progress, 0.0125), "▏░░░░░░░░░");
+            assert_eq!(bar(&progress, 0.01), "░░░░░░░░░░");
+            assert_eq!(bar(&progress, 1.0), "██████████");
+        }
+
+        #[test]
+        fn smooth_bars_fall_back_to_whole_cells() {
+            let mut progress: ProgressBar = ProgressBar::new("");
+            progress.set_length(10);
+            progress.set_smooth(true);
+            progress.set_head_glyph(Some(Glyph::new(">").unwrap()));
+            assert_eq!(bar(&progress, 0.55), "██████>░░░");
+            progress.set_head_glyph(None);
+            progress.set_leading_glyph(Glyph::new("🦀").unwrap());
+            assert_eq!(bar(&progress, 0.55), "🦀🦀🦀░░░░");
+        }
+
+        #[test]
+        fn gradients_interpolate_between_stops() {
+            let gradient: Gradient = Gradient::new(
+                vec![text::Color::Rgb(0, 0, 0), text::Color::Rgb(200, 100, 0)],
+                GradientMode::Horizontal,
+            );
+            assert_eq!(gradient.color_at(0.5), Some(text::Color::Rgb(100, 50, 0)));
+            assert_eq!(gradient.color_at(2.0), Some(text::Color::Rgb(200, 100, 0)));
+            let three: Gradient = Gradient::red_yellow_green(GradientMode::Percent);
+            assert_eq!(three.color_at(0.5), Some(text::Color::Rgb(230, 200, 40)));
+            assert_eq!(
+                Gradient::new(Vec::new(), GradientMode::Percent).color_at(0.5),
+                None
+            );
+        }
+
+        #[test]
+        fn gradients_color_the_leading_cells() {
+            let black: text::Color = text::Color::Rgb(0, 0, 0);
+            let red: text::Color = text::Color::Rgb(200, 0, 0);
+            let mut progress: ProgressBar = ProgressBar::new("");
+            progress.set_length(5);
+            progress.set_color_support(text::ColorSupport::TrueColor);
+            progress.set_gradient(Some(Gradient::new(
+                vec![black, red],
+                GradientMode::Horizontal,
+            )));
+            let reset: String = format!("{}{}", Style::Regular, Style::Regular);
+            assert_eq!(
+                bar(&progress, 0.6),
+                format!(
+                    "{}█{}█{}█{}░░",
+                    black,
+                    text::Color::Rgb(50, 0, 0),
+                    text::Color::Rgb(100, 0, 0),
+                    reset
+                )
+            );
+            progress.set_gradient(Some(Gradient::new(vec![black, red], GradientMode::Percent)));
+            assert_eq!(
+                bar(&progress, 0.6),
+                format!("{}███{}░░", text::Color::Rgb(120, 0, 0), reset)
+            );
+            progress.set_color_support(text::ColorSupport::Ansi16);
+            let ansi: text::Color = text::Color::Rgb(120, 0, 0).to_ansi16();
+            assert_eq!(bar(&progress, 0.6), format!("{}███{}░░", ansi, reset));
+            assert_eq!(bar(&progress, 0.0), "░░░░░");
+        }
+
+        #[test]
+        fn segments_use_their_own_styles() {
+            let mut progress: ProgressBar = ProgressBar::new("L ");
+            progress.set_length(2);
+            progress.show_brackets(true);
+            progress.set_percent(0.5);
+            assert_eq!(progress.to_string(), "L [█░] 50.0%");
+            progress.set_style(Style::Faint);
+            progress.set_label_style(Some(Style::Bold));
+            progress.set_bracket_style(Some(Style::Blue));
+            progress.set_leading_style(Some(Style::Green));
+            progress.set_trailing_style(Some(Style::DarkGray));
+            progress.set_percent_style(Some(Style::Yellow));
+            let r: String = format!("{}{}", Style::Regular, Style::Faint);
+            assert_eq!(
+                progress.to_string(),
+                format!(
+                    "{b}L {r}{u}[{r}{g}█{r}{d}░{r}{u}]{r} {y}50.0%{r}",
+                    b = Style::Bold,
+                    u = Style::Blue,
+                    g = Style::Green,
+                    d = Style::DarkGray,
+                    y = Style::Yellow,
+                    r = r
+                )
+            );
+        }
+
+        fn ago(secs: u64) -> time::Instant {
+            time::Instant::now()
+                .checked_sub(time::Duration::from_secs(secs))
+                .unwrap()
+        }
+
+        #[test]
+        fn stalled_bars_show_how_long_they_have_been_stalled() {
+            let mut progress: ProgressBar = ProgressBar::new("");
+            progress.set_length(2);
+            progress.set_percent(0.5);
+            progress.last_update = ago(90);
+            assert_eq!(progress.to_string(), "█░ 50.0%");
+            progress.set_stall_threshold(Some(60_000));
+            assert_eq!(progress.to_string(), "█░ 50.0% (stalled for 1m 30s)");
+            progress.set_percent(0.5);
+            assert!(progress.to_string().ends_with("(stalled for 1m 30s)"));
+            progress.set_percent(0.6);
+            assert_eq!(progress.to_string(), "█░ 60.0%");
+            progress.set_percent(1.0);
+            progress.last_update = ago(90);
+            assert_eq!(progress.to_string(), "██ 100.0%");
+        }
+
+        #[test]
+        fn paused_time_is_excluded_from_the_stats() {
+            let mut progress: ProgressBar = ProgressBar::new("");
+            progress.set_length(2);
+            progress.show_elapsed(true);
+            progress.set_stall_threshold(Some(1_000));
+            progress.set_percent(0.5);
+            progress.started = ago(100);
+            progress.last_update = ago(40);
+            progress.pause();
+            progress.pause();
+            progress.paused_at = Some(ago(30));
+            assert!(progress.is_paused());
+            assert_eq!(progress.to_string(), "█░ 50.0% 1m 10s (paused)");
+            progress.resume();
+            assert!(!progress.is_paused());
+            assert_eq!(progress.elapsed().as_secs(), 70);
+            assert_eq!(progress.eta().map(|eta| eta.as_secs()), Some(70));
+            assert_eq!(progress.last_update.elapsed().as_secs(), 10);
+            assert_eq!(progress.to_string(), "█░ 50.0% 1m 10s (stalled for 10s)");
+        }
+
+        #[test]
+        fn final_renderings_are_never_stalled() {
+            let mut bar: ProgressBar = ProgressBar::new("");
+            bar.set_length(2);
+            bar.set_percent(0.5);
+            bar.set_stall_threshold(Some(1_000));
+            bar.last_update = ago(90);
+            assert!(bar.to_string().ends_with("(stalled for 1m 30s)"));
+            let line: String = bar.final_line(Style::Yellow);
+            assert_eq!(line, "█░ 50.0%");
+            assert_eq!(bar.stall_threshold(), Some(time::Duration::from_secs(1)));
+            assert!(matches!(bar.text_style, Style::Regular));
+        }
+
+        #[test]
+        fn clearing_a_paused_bar_resumes_it() {
+            let mut bar: ProgressBar = ProgressBar::new("");
+            bar.pause();
+            bar.paused_at = Some(ago(30));
+            bar.finish_and_clear();
+            assert!(!bar.is_paused());
+            assert_eq!(bar.paused_total.as_secs(), 30);
+            assert_eq!(bar.state(), State::Finished);
+        }
+
+        #[test]
+        fn glyphs_are_validated_against_a_fixed_width() {
+            assert_eq!(Glyph::new(""), Err(GlyphError::Empty));
+            assert_eq!(Glyph::new("a\n"), Err(GlyphError::ControlChar('\n')));
+            assert_eq!(Glyph::new("\u{301}"), Err(GlyphError::ZeroWidth));
+            assert_eq!(
+                Glyph::new("=".repeat(9).as_str()),
+                Err(GlyphError::TooWide(9))
+            );
+            assert_eq!(Glyph::new("🦀").map(|g| g.width()), Ok(2));
+            assert_eq!(Glyph::new("=>").unwrap().to_bar_char(), None);
+            assert_eq!(
+                Glyph::from(BarChar::LightShade).to_bar_char(),
+                Some(BarChar::LightShade)
+            );
+        }
+
+        #[test]
+        fn bar_chars_fall_back_to_a_full_block() {
+            assert_eq!(BarChar::from('#'), BarChar::NumberSign);
+            assert_eq!(BarChar::from('x'), BarChar::FullBlock);
+            let mut bar: ProgressBar = ProgressBar::new("");
+            assert_eq!(bar.trailing_char(), BarChar::LightShade);
+            bar.set_trailing_glyph(Glyph::new("-").unwrap());
+            assert_eq!(bar.trailing_char(), BarChar::FullBlock);
+        }
+
+        #[test]
+        fn lines_are_fitted_to_the_terminal() {
+            let mut progress: ProgressBar = ProgressBar::new("Label ");
+            progress.set_length(10);
+            progress.add_child("child", 1.0);
+            progress.show_children(true);
+            let lines: Vec<String> = progress.fit_lines(80);
+            assert_eq!(lines, progress.lines());
+            assert_eq!(lines[0], "Label ░░░░░░░░░░ 0.0%");
+            let lines: Vec<String> = progress.fit_lines(18);
+            assert_eq!(lines[0], "Label ░░░░░ 0.0%");
+            assert_eq!(lines[1], "  child░░░░░ 0.0%");
+            assert_eq!(progress.length(), 10);
+            let lines: Vec<String> = progress.fit_lines(8);
+            assert!(lines.iter().all(|l| text::visible_width(l) <= 7));
+            assert!(lines[0].starts_with("Label"));
+        }
+
+        #[test]
+        fn only_the_first_ending_counts() {
+            let mut bar: ProgressBar = ProgressBar::new("");
+            bar.set_percent(0.3);
+            bar.abandon();
+            assert_eq!(bar.state(), State::Abandoned);
+            bar.finish();
+            bar.finish_with_message("done");
+            bar.finish_and_clear();
+            bar.fail("error");
+            assert_eq!(bar.state(), State::Abandoned);
+            assert!(close(bar.percent(), 0.3));
+            let mut bar: ProgressBar = ProgressBar::new("");
+            bar.finish_with_message("done");
+            assert_eq!(bar.state(), State::Finished);
+            assert!(close(bar.percent(), 1.0));
+            assert!(!bar.drawn && bar.cursor.is_none());
+        }
+
+        #[test]
+        fn clones_are_not_drawn() {
+            let mut bar: ProgressBar = ProgressBar::new("clone ");
+            bar.add_child("a", 1.0);
+            bar.set_percent(0.5);
+            bar.drawn = true;
+            let copy: ProgressBar = bar.clone();
+            bar.drawn = false;
+            assert!(!copy.drawn);
+            assert_eq!(copy.to_string(), bar.to_string());
+            assert_eq!(copy.child_count(), 1);
+        }
+
+        fn buffered(bar: &mut ProgressBar) -> Arc<Mutex<String>> {
+            let buf: Arc<Mutex<String>> = Arc::new(Mutex::new(String::new()));
+            bar.output = Output::Buffer(Arc::clone(&buf));
+            buf
+        }
+
+        fn take(buf: &Arc<Mutex<String>>) -> String {
+            std::mem::take(&mut *buf.lock().unwrap())
+        }
+
+        /// A progress bar with one sub-task that prints into a buffer, and the two lines it prints.
+        fn bar_with_child() -> (ProgressBar, Arc<Mutex<String>>, String) {
+            let mut bar: ProgressBar = ProgressBar::new("");
+            bar.set_length(2);
+            bar.add_child("c", 1.0);
+            bar.show_children(true);
+            let buf: Arc<Mutex<String>> = buffered(&mut bar);
+            let lines: String = format!("{r}░░ 0.0%{r}\n{r}  c░░ 0.0%{r}", r = Style::Regular);
+            (bar, buf, lines)
+        }
+
+        #[test]
+        fn println_prints_above_the_bar() {
+            let (mut bar, buf, lines) = bar_with_child();
+            let clear: String = format!("\r{}", Erase::Line);
+            bar.println("not drawn yet");
+            assert_eq!(take(&buf), "not drawn yet\n");
+            bar.show();
+            assert_eq!(take(&buf), format!("{}{}", clear, lines));
+            assert_eq!(bar.prev_lines, 1);
+            bar.println("log");
+            let up: Cursor = Cursor::Up(1);
+            assert_eq!(
+                take(&buf),
+                format!(
+                    "{c}{up}{c}log\n{c}{lines}",
+                    c = clear,
+                    up = up,
+                    lines = lines
+                )
+            );
+            assert_eq!(bar.prev_lines, 1);
+            assert!(bar.drawn);
+        }
+
+        #[test]
+        fn suspend_erases_the_bar_until_f_returns() {
+            let (mut bar, buf, lines) = bar_with_child();
+            bar.show();
+            take(&buf);
+            let printed: String = bar.suspend(|| take(&buf));
+            let clear: String = format!("\r{}", Erase::Line);
+            assert_eq!(printed, format!("{c}{}{c}", Cursor::Up(1), c = clear));
+            assert_eq!(take(&buf), format!("{}{}", clear, lines));
+            assert_eq!(bar.prev_lines, 1);
+            bar.finish_and_clear();
+            assert_eq!(bar.suspend(|| 7), 7);
+            take(&buf);
+            assert_eq!(bar.suspend(|| take(&buf)), "");
+            assert!(!bar.drawn);
+        }
+
+        #[test]
+        fn print_messages_are_printed_above_the_bar() {
+            let (mut bar, buf, lines) = bar_with_child();
+            bar.show();
+            take(&buf);
+            let (tx, rx) = mpsc::channel::<Message>();
+            tx.send(Message::Print(String::from("log"))).unwrap();
+            drop(tx);
+            bar.listen(&rx);
+            let clear: String = format!("\r{}", Erase::Line);
+            assert_eq!(
+                take(&buf),
+                format!("{c}{}{c}log\n{c}{}", Cursor::Up(1), lines, c = clear)
+            );
+            assert!(bar.drawn && !bar.is_finished());
+            assert_eq!(bar.prev_lines, 1);
+        }
+
+        #[test]
+        fn children_roll_up_by_weight() {
+            let mut bar: ProgressBar = ProgressBar::new("");
+            let fetch: ChildId = bar.add_child("fetch", 1.0);
+            let build: ChildId = bar.add_child("build", 3.0);
+            bar.set_child_percent(fetch, 1.0);
+            assert!(close(bar.percent(), 0.25));
+            bar.set_child_percent(build, 0.5);
+            assert!(close(bar.percent(), 0.625));
+            bar.set_child_percent(ChildId(99), 1.0);
+            assert!(close(bar.percent(), 0.625));
+            assert_eq!(bar.child_count(), 2);
+        }
+
+        #[test]
+        fn nested_children_roll_up_through_their_parent() {
+            let (tx, _rx) = mpsc::channel();
+            let mut bar: ProgressBar = ProgressBar::new("");
+            let build: ChildTracker = bar.add_child_tracker("build", 1.0, tx);
+            bar.add_child("test", 1.0);
+            let a: ChildTracker = build.add_child("a", 1.0).unwrap();
+            let b: ChildTracker = build.add_child("b", 3.0).unwrap();
+            bar.insert_child(b.id(), Some(build.id()), "b", 3.0);
+            bar.insert_child(a.id(), Some(build.id()), "a", 1.0);
+            bar.set_child_percent(b.id(), 1.0);
+            assert_eq!(bar.child_percent(build.id()), Some(0.75));
+            assert!(close(bar.percent(), 0.375));
+            // the percentage of a parent always comes from its sub-tasks
+            bar.set_child_percent(build.id(), 0.0);
+            assert!(close(bar.percent(), 0.375));
+        }
+
+        #[test]
+        fn children_with_bogus_indexes_are_ignored() {
+            let mut bar: ProgressBar = ProgressBar::new("");
+            bar.insert_child(ChildId(usize::MAX), None, "huge", 1.0);
+            bar.insert_child(ChildId(5), None, "gap", 1.0);
+            assert_eq!(bar.child_count(), 0);
+            bar.insert_child(ChildId(0), None, "first", 1.0);
+            bar.insert_child(ChildId(1), None, "next", 1.0);
+            assert_eq!(bar.child_count(), 2);
+            assert_eq!(bar.add_child("after", 1.0), ChildId(2));
+        }
+
+        #[test]
+        fn children_with_bad_slots_or_parents_are_ignored() {
+            let mut bar: ProgressBar = ProgressBar::new("");
+            let first: ChildId = bar.add_child("first", 1.0);
+            bar.set_child_percent(first, 0.5);
+            bar.insert_child(first, None, "again", 3.0);
+            assert_eq!(bar.child_percent(first), Some(0.5));
+            bar.next_child.store(4, Ordering::Relaxed);
+            // the parent must be filled and come before the sub-task
+            bar.insert_child(ChildId(1), Some(ChildId(1)), "itself", 1.0);
+            bar.insert_child(ChildId(1), Some(ChildId(2)), "later", 1.0);
+            bar.insert_child(ChildId(3), Some(ChildId(2)), "orphan", 1.0);
+            assert_eq!(bar.child_count(), 1);
+            bar.insert_child(ChildId(2), Some(first), "nested", 1.0);
+            bar.insert_child(ChildId(3), Some(ChildId(2)), "deeper", 1.0);
+            assert_eq!(bar.child_count(), 3);
+            assert_eq!(bar.child_percent(first), Some(0.0));
+            assert!(close(bar.percent(), 0.0));
+        }
+    }
+}
+
+/// A module for formatting numbers, sizes, and durations in a human-readable way. Each type
+/// in this module is a thin wrapper that implements ```Display```. The number of decimal places
+/// can be controlled with the precision of the format string, e.g. ```format!("{:.2}", ...)```.
+pub mod format {
+
+    use std::fmt;
+    use std::time;
+
+    const BINARY_UNITS: [&str; 7] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+    const DECIMAL_UNITS: [&str; 7] = ["B", "kB", "MB", "GB", "TB", "PB", "EB"];
+    const SI_UNITS: [&str; 7] = ["", "k", "M", "G", "T", "P", "E"];
+
+    /// A private function that scales ```value``` down by ```base``` until it is less than ```base```
+    /// once rounded to the precision, and writes it with the matching unit. Whole values that do
+    /// not need to be scaled are written without any decimal places.
+    fn write_scaled(
+        f: &mut fmt::Formatter<'_>,
+        value: f64,
+        base: f64,
+        units: &[&str],
+        separator: &str,
+        suffix: &str,
+        default_precision: usize,
+    ) -> fmt::Result {
+        let precision: usize = f.precision().unwrap_or(default_precision);
+        let scale: f64 = 10f64.powi(precision.min(15) as i32);
+        let mut value: f64 = value;
+        let mut index: usize = 0;
+        while (value * scale).round().abs() >= base * scale && index < units.len() - 1 {
+            value /= base;
+            index += 1;
+        }
+        if index == 0 && value.fract() == 0.0 {
+            write!(f, "{}{}{}{}", value, separator, units[index], suffix)
+        } else {
+            write!(
+                f,
+                "{:.*}{}{}{}",
+                precision, value, separator, units[index], suffix
+            )
+        }
+    }
+
+    /// A number of bytes formatted with binary (IEC) units, e.g. "1.50 KiB". The default
+    /// precision is 2 decimal places.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct BinaryBytes(pub u64);
+
+    impl fmt::Display for BinaryBytes {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write_scaled(f, self.0 as f64, 1024.0, &BINARY_UNITS, " ", "", 2)
+        }
+    }
+
+    /// A number of bytes formatted with decimal (SI) units, e.g. "1.50 kB". The default
+    /// precision is 2 decimal places.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct DecimalBytes(pub u64);
+
+    impl fmt::Display for DecimalBytes {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write_scaled(f, self.0 as f64, 1000.0, &DECIMAL_UNITS, " ", "", 2)
+        }
+    }
+
+    /// A count of things formatted with SI prefixes, e.g. "1.2k" or "3.4M". The default
+    /// precision is 1 decimal place.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct Count(pub u64);
+
+    impl fmt::Display for Count {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write_scaled(f, self.0 as f64, 1000.0, &SI_UNITS, "", "", 1)
+        }
+    }
+
+    /// A duration formatted using its two most significant units, e.g. "2h 03m", "4m 05s",
+    /// "12.3s" or "450ms". The precision only applies to durations shorter than one minute
+    /// and defaults to 1 decimal place.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct HumanDuration(pub time::Duration);
+
+    impl fmt::Display for HumanDuration {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let precision: usize = f.precision().unwrap_or(1);
+            let scale: f64 = 10f64.powi(precision.min(15) as i32);
+            // a duration just under a minute would otherwise be printed as "60.0s"
+            let secs: u64 = if (self.0.as_secs_f64() * scale).round() >= 60.0 * scale {
+                self.0.as_secs().max(60)
+            } else {
+                self.0.as_secs()
+            };
+            if secs >= 86400 {
+                write!(f, "{}d {:02}h", secs / 86400, (secs % 86400) / 3600)
+            } else if secs >= 3600 {
+                write!(f, "{}h {:02}m", secs / 3600, (secs % 3600) / 60)
+            } else if secs >= 60 {
+                write!(f, "{}m {:02}s", secs / 60, secs % 60)
+            } else if secs >= 1 {
+                write!(f, "{:.*}s", precision, self.0.as_secs_f64())
+            } else {
+                write!(f, "{}ms", self.0.as_millis())
+            }
+        }
+    }
+
+    /// A point in time relative to now, formatted as "just now", "3 minutes ago" or
+    /// "in 2 hours". Only the most significant unit is shown and the value is rounded down.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct HumanTime {
+        offset: time::Duration,
+        future: bool,
+    }
+
+    impl HumanTime {
+        /// Creates a new ```HumanTime``` for an event that happened ```elapsed``` ago.
+        pub fn ago(elapsed: time::Duration) -> Self {
+            Self {
+                offset: elapsed,
+                future: false,
+            }
+        }
+
+        /// Creates a new ```HumanTime``` for an event that will happen after ```remaining```.
+        pub fn from_now(remaining: time::Duration) -> Self {
+            Self {
+                offset: remaining,
+                future: true,
+            }
+        }
+
+        /// Creates a new ```HumanTime``` by comparing ```t``` to the current system time.
+        pub fn since(t: time::SystemTime) -> Self {
+            match time::SystemTime::now().duration_since(t) {
+                Ok(elapsed) => Self::ago(elapsed),
+                Err(e) => Self::from_now(e.duration()),
+            }
+        }
+    }
+
+    impl fmt::Display for HumanTime {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            const UNITS: [(u64, &str); 6] = [
+                (365 * 86400, "year"),
+                (30 * 86400, "month"),
+                (7 * 86400, "week"),
+                (86400, "day"),
+                (3600, "hour"),
+                (60, "minute"),
+            ];
+            let secs: u64 = self.offset.as_secs();
+            let (n, unit) = UNITS
+                .iter()
+                .find(|(size, _)| secs >= *size)
+                .map(|(size, unit)| (secs / size, *unit))
+                .unwrap_or((secs, "second"));
+            if n == 0 || (unit == "second" && n < 10) {
+                return f.write_str("just now");
+            }
+            let plural: &str = if n == 1 { "" } else { "s" };
+            if self.future {
+                write!(f, "in {} {}{}", n, unit, plural)
+            } else {
+                write!(f, "{} {}{} ago", n, unit, plural)
+            }
+        }
+    }
+
+    /// The unit of the amount passed to ```Rate```.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub enum RateUnit {
+        BinaryBytes,
+        DecimalBytes,
+        Count,
+    }
+
+    /// An amount per second formatted with the same units as ```BinaryBytes```, ```DecimalBytes```
+    /// or ```Count```, e.g. "1.50 MiB/s" or "1.2k/s".
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    pub struct Rate {
+        per_second: f64,
+        unit: RateUnit,
+    }
+
+    impl Rate {
+        /// Creates a new ```Rate``` from an ```amount``` that accumulated over ```elapsed```.
+        /// If ```elapsed``` is zero, then the rate is zero.
+        pub fn new(amount: f64, elapsed: time::Duration, unit: RateUnit) -> Self {
+            let secs: f64 = elapsed.as_secs_f64();
+            Self {
+                per_second: if secs > 0.0 { amount / secs } else { 0.0 },
+                unit,
+            }
+        }
+
+        /// Returns the rate as an amount per second.
+        pub fn per_second(&self) -> f64 {
+            self.per_second
+        }
+    }
+
+    impl fmt::Display for Rate {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self.unit {
+                RateUnit::BinaryBytes => {
+                    write_scaled(f, self.per_second, 1024.0, &BINARY_UNITS, " ", "/s", 2)
+                }
+                RateUnit::DecimalBytes => {
+                    write_scaled(f, self.per_second, 1000.0, &DECIMAL_UNITS, " ", "/s", 2)
+                }
+                RateUnit::Count => write_scaled(f, self.per_second, 1000.0, &SI_UNITS, "", "/s", 1),
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+
+        use super::*;
+
+        #[test]
+        fn bytes_round_before_choosing_the_unit() {
+            assert_eq!(BinaryBytes(0).to_string(), "0 B");
+            assert_eq!(BinaryBytes(1023).to_string(), "1023 B");
+            assert_eq!(BinaryBytes(1024).to_string(), "1.00 KiB");
+            assert_eq!(BinaryBytes(1536).to_string(), "1.50 KiB");
+            assert_eq!(BinaryBytes(1_048_575).to_string(), "1.00 MiB");
+            assert_eq!(BinaryBytes(u64::MAX).to_string(), "16.00 EiB");
+            assert_eq!(DecimalBytes(999_994).to_string(), "999.99 kB");
+            assert_eq!(DecimalBytes(999_995).to_string(), "1.00 MB");
+        }
+
+        #[test]
+        fn precision_changes_where_values_round_up() {
+            assert_eq!(format!("{:.0}", BinaryBytes(1535)), "1 KiB");
+            assert_eq!(format!("{:.0}", BinaryBytes(1_048_064)), "1 MiB");
+            assert_eq!(format!("{:.3}", DecimalBytes(999_995)), "999.995 kB");
+        }
+
+        #[test]
+        fn counts_use_si_prefixes() {
+            assert_eq!(Count(999).to_string(), "999");
+            assert_eq!(Count(1_200).to_string(), "1.2k");
+            assert_eq!(Count(999_949).to_string(), "999.9k");
+            assert_eq!(Count(999_950).to_string(), "1.0M");
+            assert_eq!(Count(999_999).to_string(), "1.0M");
+        }
+
+        #[test]
+        fn durations_show_two_units() {
+            let duration = |millis: u64| HumanDuration(time::Duration::from_millis(millis));
+            assert_eq!(duration(450).to_string(), "450ms");
+            assert_eq!(duration(1_500).to_string(), "1.5s");
+            assert_eq!(duration(59_940).to_string(), "59.9s");
+            assert_eq!(duration(59_960).to_string(), "1m 00s");
+            assert_eq!(format!("{:.2}", duration(59_994)), "59.99s");
+            assert_eq!(duration(3_723_000).to_string(), "1h 02m");
+            assert_eq!(duration(90_000_000).to_string(), "1d 01h");
+        }
+
+        #[test]
+        fn human_time_shows_the_most_significant_unit() {
+            let secs = time::Duration::from_secs;
+            assert_eq!(HumanTime::ago(secs(5)).to_string(), "just now");
+            assert_eq!(HumanTime::ago(secs(30)).to_string(), "30 seconds ago");
+            assert_eq!(HumanTime::ago(secs(119)).to_string(), "1 minute ago");
+            assert_eq!(HumanTime::from_now(secs(7_200)).to_string(), "in 2 hours");
+        }
+
+        #[test]
+        fn rates_round_before_choosing_the_unit() {
+            let second: time::Duration = time::Duration::from_secs(1);
+            assert_eq!(
+                Rate::new(1536.0, second, RateUnit::BinaryBytes).to_string(),
+                "1.50 KiB/s"
+            );
+            assert_eq!(
+                Rate::new(999.96, second, RateUnit::Count).to_string(),
+                "1.0k/s"
+            );
+            assert_eq!(
+                Rate::new(10.0, time::Duration::ZERO, RateUnit::Count).to_string(),
+                "0/s"
+            );
+        }
+    }
+}
+
+/// A module for controlling the cursor and the screen of the terminal through stdout. Like
+/// ```text::Style```, each command is an enum that implements ```Display``` by writing the
+/// matching ANSI escape sequence, so commands can be mixed freely with text in ```print!()```.
+pub mod term {
+
+    use std::fmt;
+    use std::fmt::Display;
+    use std::io::Write;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{mpsc, Mutex, Once};
+    use std::thread;
+
+    /// An enum of commands that move, save, restore, hide, or show the cursor. Rows and columns
+    /// are numbered from 0. Moving the cursor by 0 rows or columns does nothing.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Cursor {
+        Up(u16),
+        Down(u16),
+        Left(u16),
+        Right(u16),
+        /// Moves the cursor to the beginning of the line n lines down.
+        NextLine(u16),
+        /// Moves the cursor to the beginning of the line n lines up.
+        PrevLine(u16),
+        ToColumn(u16),
+        /// Moves the cursor to (row, column).
+        To(u16, u16),
+        Save,
+        Restore,
+        Hide,
+        Show,
+    }
+
+    impl Display for Cursor {
+        /// Formats a term::Cursor as a string so it can printed to stdout
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match *self {
+                Cursor::Up(0)
+                | Cursor::Down(0)
+                | Cursor::Left(0)
+                | Cursor::Right(0)
+                | Cursor::NextLine(0)
+                | Cursor::PrevLine(0) => Ok(()),
+                Cursor::Up(n) => write!(f, "\x1b[{}A", n),
+                Cursor::Down(n) => write!(f, "\x1b[{}B", n),
+                Cursor::Right(n) => write!(f, "\x1b[{}C", n),
+                Cursor::Left(n) => write!(f, "\x1b[{}D", n),
+                Cursor::NextLine(n) => write!(f, "\x1b[{}E", n),
+                Cursor::PrevLine(n) => write!(f, "\x1b[{}F", n),
+                Cursor::ToColumn(col) => write!(f, "\x1b[{}G", col as u32 + 1),
+                Cursor::To(row, col) => write!(f, "\x1b[{};{}H", row as u32 + 1, col as u32 + 1),
+                Cursor::Save => f.write_str("\x1b7"),
+                Cursor::Restore => f.write_str("\x1b8"),
+                Cursor::Hide => f.write_str("\x1b[?25l"),
+                Cursor::Show => f.write_str("\x1b[?25h"),
+            }
+        }
+    }
+
+    /// An enum of commands that erase part of the current line or of the screen. None of them
+    /// move the cursor.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Erase {
+        /// Erases from the cursor to the end of the line.
+        LineToEnd,
+        /// Erases from the beginning of the line to the cursor.
+        LineToStart,
+        /// Erases the entire line.
+        Line,
+        /// Erases from the cursor to the end of the screen.
+        ScreenToEnd,
+        /// Erases from the beginning of the screen to the cursor.
+        ScreenToStart,
+        /// Erases the entire screen.
+        Screen,
+        /// Erases the lines that have scrolled off the top of the screen.
+        Scrollback,
+    }
+
+    impl Display for Erase {
+        /// Formats a term::Erase as a string so it can printed to stdout
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(match self {
+                Erase::LineToEnd => "\x1b[0K",
+                Erase::LineToStart => "\x1b[1K",
+                Erase::Line => "\x1b[2K",
+                Erase::ScreenToEnd => "\x1b[0J",
+                Erase::ScreenToStart => "\x1b[1J",
+                Erase::Screen => "\x1b[2J",
+                Erase::Scrollback => "\x1b[3J",
+            })
+        }
+    }
+
+    /// An enum of commands that scroll the screen or switch between the main and alternate
+    /// screens. Rows are numbered from 0.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Screen {
+        /// Restricts scrolling to the rows from top to bottom, inclusive.
+        ScrollRegion(u16, u16),
+        /// Allows the entire screen to scroll again.
+        ResetScrollRegion,
+        /// Scrolls the contents of the scroll region up by n lines.
+        ScrollUp(u16),
+        /// Scrolls the contents of the scroll region down by n lines.
+        ScrollDown(u16),
+        /// Switches to the alternate screen, which is typically used by full-screen programs so
+        /// that the contents of the main screen are restored when they exit.
+        EnterAlternate,
+        /// Switches back to the main screen.
+        LeaveAlternate,
+        /// Asks the terminal to surround pasted text with escape sequences, so that
+        /// ```input::KeyReader``` can tell it apart from typed text.
+        EnableBracketedPaste,
+        /// Asks the terminal to stop surrounding pasted text with escape sequences.
+        DisableBracketedPaste,
+    }
+
+    impl Display for Screen {
+        /// Formats a term::Screen as a string so it can printed to stdout
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match *self {
+                Screen::ScrollRegion(top, bottom) => {
+                    write!(f, "\x1b[{};{}r", top as u32 + 1, bottom as u32 + 1)
+                }
+                Screen::ResetScrollRegion => f.write_str("\x1b[r"),
+                Screen::ScrollUp(0) | Screen::ScrollDown(0) => Ok(()),
+                Screen::ScrollUp(n) => write!(f, "\x1b[{}S", n),
+                Screen::ScrollDown(n) => write!(f, "\x1b[{}T", n),
+                Screen::EnterAlternate => f.write_str("\x1b[?1049h"),
+                Screen::LeaveAlternate => f.write_str("\x1b[?1049l"),
+                Screen::EnableBracketedPaste => f.write_str("\x1b[?2004h"),
+                Screen::DisableBracketedPaste => f.write_str("\x1b[?2004l"),
+            }
+        }
+    }
+
+    /// The number of CursorGuards that are alive. The cursor is hidden while this is non-zero.
+    static HIDDEN_CURSORS: AtomicUsize = AtomicUsize::new(0);
+
+    /// Ensures that the restore hooks are only installed once.
+    static RESTORE_HOOKS: Once = Once::new();
+
+    /// The threads that created a guard that changes the terminal, such as a CursorGuard, that
+    /// is still alive. A thread appears once for each of its guards.
+    static GUARD_THREADS: Mutex<Vec<thread::ThreadId>> = Mutex::new(Vec::new());
+
+    /// A private struct that records the thread that created a guard in ```GUARD_THREADS``` for
+    /// as long as the guard is alive, so that a panic on that thread restores the terminal.
+    #[derive(Debug)]
+    struct GuardThread(thread::ThreadId);
+
+    impl GuardThread {
+        /// Records the current thread.
+        fn register() -> Self {
+            let id: thread::ThreadId = thread::current().id();
+            guard_threads().push(id);
+            Self(id)
+        }
+    }
+
+    impl Drop for GuardThread {
+        fn drop(&mut self) {
+            let mut threads = guard_threads();
+            if let Some(i) = threads.iter().position(|id| *id == self.0) {
+                threads.swap_remove(i);
+            }
+        }
+    }
+
+    /// A private function that locks ```GUARD_THREADS```, ignoring a panic in another thread.
+    fn guard_threads() -> std::sync::MutexGuard<'static, Vec<thread::ThreadId>> {
+        GUARD_THREADS.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// A private function that returns ```true``` if a panic on ```thread``` should restore the
+    /// terminal, which is the case for the main thread, whose panic ends the program, and for a
+    /// thread that created a guard that is still alive.
+    fn restores_on_panic(thread: &thread::Thread) -> bool {
+        thread.name() == Some("main") || guard_threads().contains(&thread.id())
+    }
+
+    /// A guard that hides the cursor for as long as it is alive and shows it again when it is
+    /// dropped. Guards can be nested, in which case the cursor is shown again once the last guard
+    /// is dropped. Creating a guard also calls ```term::install_restore_hooks()```, so the cursor
+    /// is shown again even if the program panics or is interrupted with Ctrl-C.
+    #[derive(Debug)]
+    pub struct CursorGuard {
+        _thread: GuardThread,
+    }
+
+    impl CursorGuard {
+        /// Hides the cursor and returns a guard that shows it again when dropped.
+        pub fn hide() -> Self {
+            install_restore_hooks();
+            if HIDDEN_CURSORS.fetch_add(1, Ordering::SeqCst) == 0 {
+                print!("{}", Cursor::Hide);
+                let _ = std::io::stdout().flush();
+            }
+            Self {
+                _thread: GuardThread::register(),
+            }
+        }
+    }
+
+    impl Drop for CursorGuard {
+        /// Shows the cursor again if this is the last guard that is alive.
+        fn drop(&mut self) {
+            if HIDDEN_CURSORS.fetch_sub(1, Ordering::SeqCst) == 1 {
+                print!("{}", Cursor::Show);
+                let _ = std::io::stdout().flush();
+            }
+        }
+    }
+
+    /// Installs a panic hook and SIGINT/SIGTERM handlers that put the terminal back into a usable
+    /// state before the program exits, e.g. by showing the cursor if it was hidden by a
+    /// ```CursorGuard``` or by restoring the terminal attributes changed by ```input::RawMode```.
+    /// Only panics on the main thread, or on a thread that created one of these guards that is
+    /// still alive, restore the terminal, because a panic on another thread does not end the
+    /// program and the terminal may still be in use. The previous panic hook is called either
+    /// way. The signals are then handed on to the handlers the program had installed before, or
+    /// terminate the program if there were none, and signals the program ignores stay ignored.
+    /// Handlers installed by the program after this function replace these hooks. Calling this
+    /// function more than once has no effect.
+    pub fn install_restore_hooks() {
+        RESTORE_HOOKS.call_once(|| {
+            let prev = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                if restores_on_panic(&thread::current()) {
+                    restore();
+                }
+                prev(info);
+            }));
+            sys::install_signal_handlers(on_signal);
+        });
+    }
+
+    /// A private function that undoes any changes made to the terminal. It only uses
+    /// async-signal-safe calls, so it can be called from a signal handler, and it writes straight
+    /// to the file descriptor because stdout may be locked by the thread that was interrupted.
+    fn restore() {
+        sys::restore_original();
+        if HIDDEN_CURSORS.load(Ordering::SeqCst) > 0 {
+            sys::write_stdout(b"\x1b[?25h");
+        }
+    }
+
+    /// A private signal handler that restores the terminal and then raises the signal again
+    /// with the disposition it had before the restore hooks were installed. The handler stays
+    /// installed if the program's own handler returns.
+    extern "C" fn on_signal(signal: std::os::raw::c_int) {
+        restore();
+        sys::raise_previous(signal, on_signal);
+    }
+
+    /// The size of the terminal in columns and rows.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Size {
+        pub cols: u16,
+        pub rows: u16,
+    }
+
+    /// Returns the size of the terminal attached to stdout, stderr, or stdin, in that order. If
+    /// none of them is a terminal, then the size is read from the ```COLUMNS``` and ```LINES```
+    /// environment variables. Returns ```None``` if the size cannot be determined.
+    pub fn size() -> Option<Size> {
+        sys::window_size().or_else(|| {
+            let var = |name: &str| std::env::var(name).ok()?.trim().parse::<u16>().ok();
+            match (var("COLUMNS"), var("LINES")) {
+                (Some(cols), Some(rows)) if cols > 0 && rows > 0 => Some(Size { cols, rows }),
+                _ => None,
+            }
+        })
+    }
+
+    /// The senders of every receiver returned by ```term::resize_events()```.
+    static RESIZE_WATCHERS: Mutex<Vec<mpsc::Sender<Size>>> = Mutex::new(Vec::new());
+
+    /// Ensures that the SIGWINCH handler and the thread that delivers resize events are only
+    /// started once.
+    static RESIZE_THREAD: Once = Once::new();
+
+    /// Returns a receiver that gets the new size of the terminal every time it is resized. The
+    /// first call installs a SIGWINCH handler and starts a background thread that delivers the
+    /// events, so the receiver can be used with ```recv()```, ```recv_timeout()``` or ```try_recv()```
+    /// like any other channel. Several receivers can be alive at the same time. On platforms
+    /// without SIGWINCH, the receiver never gets any events.
+    pub fn resize_events() -> mpsc::Receiver<Size> {
+        let (tx, rx) = mpsc::channel::<Size>();
+        RESIZE_WATCHERS.lock().unwrap().push(tx);
+        RESIZE_THREAD.call_once(|| {
+            if let Some(signals) = sys::watch_resize() {
+                thread::spawn(move || {
+                    // each byte in the pipe is one SIGWINCH
+                    for _ in signals {
+                        if let Some(size) = size() {
+                            let mut watchers = RESIZE_WATCHERS.lock().unwrap();
+                            watchers.retain(|tx| tx.send(size).is_ok());
+                        }
+                    }
+                });
+            }
+        });
+        rx
+    }
+
+    /// A module for reading keyboard input from the terminal. ```RawMode``` switches the terminal
+    /// into raw mode so that every key press is delivered immediately, and ```KeyReader``` decodes
+    /// the bytes and escape sequences sent by the terminal into ```KeyEvent```s.
+    ///
+    /// ```no_run
+    /// use cli_tools::term::input::{KeyCode, KeyEvent, KeyReader, Modifiers, RawMode};
+    ///
+    /// let _raw = RawMode::enable().unwrap();
+    /// let mut keys = KeyReader::stdin();
+    /// loop {
+    ///     match keys.read_key().unwrap() {
+    ///         KeyEvent::Key(KeyCode::Char('c'), Modifiers::CTRL) => break,
+    ///         event => println!("{:?}", event),
+    ///     }
+    /// }
+    /// ```
+    pub mod input {
+
+        use super::sys;
+        use std::fmt;
+        use std::io;
+        use std::io::Read;
+        use std::ops::BitOr;
+
+        /// A guard that switches the terminal attached to stdin into raw mode for as long as it
+        /// is alive. In raw mode, input is not buffered into lines, key presses are not echoed,
+        /// and Ctrl-C is delivered as a key press instead of a signal. Output processing is left
+        /// on, so "\n" still moves the cursor to the beginning of the next line. The original
+        /// attributes are restored when the guard is dropped and, via
+        /// ```term::install_restore_hooks()```, if the program panics or receives SIGTERM.
+        pub struct RawMode {
+            original: sys::Termios,
+            saved: bool,
+            _thread: super::GuardThread,
+        }
+
+        impl RawMode {
+            /// Switches the terminal into raw mode. Returns an error if stdin is not a terminal.
+            pub fn enable() -> io::Result<Self> {
+                let (original, saved) = change_attr(sys::make_raw)?;
+                Ok(Self {
+                    original,
+                    saved,
+                    _thread: super::GuardThread::register(),
+                })
+            }
+        }
+
+        impl fmt::Debug for RawMode {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("RawMode")
+            }
+        }
+
+        impl Drop for RawMode {
+            /// Restores the attributes the terminal had before raw mode was enabled.
+            fn drop(&mut self) {
+                restore_attr(&self.original, self.saved);
+            }
+        }
+
+        /// A guard that stops the terminal attached to stdin from echoing key presses for as long
+        /// as it is alive. Unlike ```RawMode```, input is still buffered into lines and Ctrl-C still
+        /// sends SIGINT. The original attributes are restored when the guard is dropped and, via
+        /// ```term::install_restore_hooks()```, if the program panics or is interrupted.
+        pub struct EchoOff {
+            original: sys::Termios,
+            saved: bool,
+            _thread: super::GuardThread,
+        }
+
+        impl EchoOff {
+            /// Turns echo off. Returns an error if stdin is not a terminal.
+            pub fn enable() -> io::Result<Self> {
+                let (original, saved) = change_attr(|t| sys::set_echo(t, false))?;
+                Ok(Self {
+                    original,
+                    saved,
+                    _thread: super::GuardThread::register(),
+                })
+            }
+        }
+
+        impl fmt::Debug for EchoOff {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("EchoOff")
+            }
+        }
+
+        impl Drop for EchoOff {
+            /// Restores the attributes the terminal had before echo was turned off.
+            fn drop(&mut self) {
+                restore_attr(&self.original, self.saved);
+            }
+        }
+
+        /// A private function that applies ```change``` to the attributes of stdin. Returns the
+        /// previous attributes and whether they were saved as the original attributes that are
+        /// restored if the program panics or is interrupted.
+        fn change_attr<F: FnOnce(&mut sys::Termios)>(
+            change: F,
+        ) -> io::Result<(sys::Termios, bool)> {
+            super::install_restore_hooks();
+            let original: sys::Termios = sys::get_attr()?;
+            let mut changed: sys::Termios = original;
+            change(&mut changed);
+            let saved: bool = sys::save_original(&original);
+            if let Err(e) = sys::set_attr(&changed) {
+                if saved {
+                    sys::clear_original();
+                }
+                return Err(e);
+            }
+            Ok((original, saved))
+        }
+
+        /// A private function that undoes ```change_attr()```.
+        fn restore_attr(original: &sys::Termios, saved: bool) {
+            let _ = sys::set_attr(original);
+            if saved {
+                sys::clear_original();
+            }
+        }
+
+        /// A set of modifier keys that were held down during a key press.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+        pub struct Modifiers(u8);
+
+        impl Modifiers {
+            pub const NONE: Modifiers = Modifiers(0);
+            pub const SHIFT: Modifiers = Modifiers(1);
+            pub const ALT: Modifiers = Modifiers(2);
+            pub const CTRL: Modifiers = Modifiers(4);
+
+            /// Returns ```true``` if all of the modifiers in ```other``` are held down.
+            pub fn contains(self, other: Modifiers) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            /// Returns ```true``` if no modifiers are held down.
+            pub fn is_empty(self) -> bool {
+                self.0 == 0
+            }
+
+            /// A private function that decodes the modifier parameter of a CSI sequence, which is
+            /// one more than a bit mask of shift (1), alt (2), and ctrl (4).
+            fn from_param(param: u32) -> Modifiers {
+                Modifiers((param.saturating_sub(1) & 0x7) as u8)
+            }
+        }
+
+        impl BitOr for Modifiers {
+            type Output = Modifiers;
+
+            fn bitor(self, rhs: Modifiers) -> Modifiers {
+                Modifiers(self.0 | rhs.0)
+            }
+        }
+
+        /// A key on the keyboard. Letters are reported in the case they were typed, except
+        /// when Ctrl is held down, in which case they are always lowercase.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum KeyCode {
+            Char(char),
+            Enter,
+            Tab,
+            BackTab,
+            Backspace,
+            Esc,
+            Up,
+            Down,
+            Left,
+            Right,
+            Home,
+            End,
+            PageUp,
+            PageDown,
+            Insert,
+            Delete,
+            /// A function key from F1 to F12.
+            F(u8),
+        }
+
+        /// An event decoded from the input of the terminal.
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum KeyEvent {
+            /// A key press and the modifiers that were held down.
+            Key(KeyCode, Modifiers),
+            /// Text that was pasted while bracketed paste was enabled. See
+            /// ```term::Screen::EnableBracketedPaste```.
+            Paste(String),
+        }
+
+        impl KeyEvent {
+            /// Creates a key press event without any modifiers.
+            pub fn key(code: KeyCode) -> Self {
+                KeyEvent::Key(code, Modifiers::NONE)
+            }
+        }
+
+        /// The result of decoding the beginning of a buffer.
+        #[derive(Debug, PartialEq, Eq)]
+        enum Parse {
+            /// An event was decoded from the given number of bytes.
+            Event(KeyEvent, usize),
+            /// More bytes are needed to decode an event.
+            Incomplete,
+            /// The given number of bytes could not be decoded and should be skipped.
+            Invalid(usize),
+        }
+
+        const ESC: u8 = 0x1b;
+        const PASTE_END: &[u8] = b"\x1b[201~";
+
+        /// A private function that decodes a single byte that is not part of an escape sequence
+        /// or a multi-byte UTF-8 char.
+        fn parse_byte(b: u8) -> KeyEvent {
+            match b {
+                b'\r' | b'\n' => KeyEvent::key(KeyCode::Enter),
+                b'\t' => KeyEvent::key(KeyCode::Tab),
+                0x7f | 0x08 => KeyEvent::key(KeyCode::Backspace),
+                ESC => KeyEvent::key(KeyCode::Esc),
+                0x00 => KeyEvent::Key(KeyCode::Char(' '), Modifiers::CTRL),
+                0x01..=0x1a => {
+                    KeyEvent::Key(KeyCode::Char((b'a' + b - 1) as char), Modifiers::CTRL)
+                }
+                0x1c..=0x1f => {
+                    KeyEvent::Key(KeyCode::Char((b'\\' + b - 0x1c) as char), Modifiers::CTRL)
+                }
+                _ => KeyEvent::key(KeyCode::Char(b as char)),
+            }
+        }
+
+        /// A private function that decodes the event at the beginning of ```buf```.
+        fn parse(buf: &[u8]) -> Parse {
+            match buf.first() {
+                None => Parse::Incomplete,
+                Some(&ESC) => parse_escape(buf),
+                Some(&b) if b < 0x80 => Parse::Event(parse_byte(b), 1),
+                Some(&b) => parse_utf8(buf, b),
+            }
+        }
+
+        /// A private function that decodes a multi-byte UTF-8 char starting with ```first```.
+        fn parse_utf8(buf: &[u8], first: u8) -> Parse {
+            let len: usize = match first {
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf7 => 4,
+                _ => return Parse::Invalid(1),
+            };
+            if buf.len() < len {
+                return Parse::Incomplete;
+            }
+            match std::str::from_utf8(&buf[..len]) {
+                Ok(s) => Parse::Event(KeyEvent::key(KeyCode::Char(s.chars().next().unwrap())), len),
+                Err(_) => Parse::Invalid(1),
+            }
+        }
+
+        /// A private function that decodes an event that starts with an escape byte.
+        fn parse_escape(buf: &[u8]) -> Parse {
+            match buf.get(1) {
+                None => Parse::Incomplete,
+                Some(b'[') => parse_csi(buf),
+                Some(b'O') => match buf.get(2) {
+                    None => Parse::Incomplete,
+                    Some(&b) => match final_key(b) {
+                        Some(code) => Parse::Event(KeyEvent::key(code), 3),
+                        None => Parse::Invalid(3),
+                    },
+                },
+                // an escape byte followed by another one is the Esc key, so a run of escape bytes
+                // is the same number of Esc keys however it was split across reads. Only an
+                // escape byte in front of a CSI or SS3 sequence means alt was held down.
+                Some(&ESC) if !matches!(buf.get(2), Some(b'[' | b'O')) => {
+                    Parse::Event(KeyEvent::key(KeyCode::Esc), 1)
+                }
+                // an escape byte followed by anything else means alt was held down, unless the
+                // rest cannot be decoded as a key, in which case the escape byte is the Esc key
+                Some(&next) => match parse(&buf[1..]) {
+                    Parse::Event(KeyEvent::Key(code, mods), n) => {
+                        Parse::Event(KeyEvent::Key(code, mods | Modifiers::ALT), n + 1)
+                    }
+                    Parse::Incomplete if next != ESC => Parse::Incomplete,
+                    _ => Parse::Event(KeyEvent::key(KeyCode::Esc), 1),
+                },
+            }
+        }
+
+        /// A private function that maps the final byte of an SS3 or CSI sequence to a key.
+        fn final_key(b: u8) -> Option<KeyCode> {
+            match b {
+                b'A' => Some(KeyCode::Up),
+                b'B' => Some(KeyCode::Down),
+                b'C' => Some(KeyCode::Right),
+                b'D' => Some(KeyCode::Left),
+                b'H' => Some(KeyCode::Home),
+                b'F' => Some(KeyCode::End),
+                b'P' => Some(KeyCode::F(1)),
+                b'Q' => Some(KeyCode::F(2)),
+                b'R' => Some(KeyCode::F(3)),
+                b'S' => Some(KeyCode::F(4)),
+                _ => None,
+            }
+        }
+
+        /// A private function that maps the first parameter of a CSI sequence ending in "~" to a key.
+        fn tilde_key(param: u32) -> Option<KeyCode> {
+            match param {
+                1 | 7 => Some(KeyCode::Home),
+                2 => Some(KeyCode::Insert),
+                3 => Some(KeyCode::Delete),
+                4 | 8 => Some(KeyCode::End),
+                5 => Some(KeyCode::PageUp),
+                6 => Some(KeyCode::PageDown),
+                11..=15 => Some(KeyCode::F((param - 10) as u8)),
+                17..=21 => Some(KeyCode::F((param - 11) as u8)),
+                23 | 24 => Some(KeyCode::F((param - 12) as u8)),
+                _ => None,
+            }
+        }
+
+        /// A private function that decodes a CSI sequence (```ESC [ params final```), including
+        /// the start of a bracketed paste.
+        fn parse_csi(buf: &[u8]) -> Parse {
+            let end: usize = match buf[2..].iter().position(|b| (0x40..=0x7e).contains(b)) {
+                Some(i) => i + 2,
+                None => return Parse::Incomplete,
+            };
+            let params: Vec<u32> = std::str::from_utf8(&buf[2..end])
+                .unwrap_or("")
+                .split(';')
+                .map(|p| p.parse::<u32>().unwrap_or(0))
+                .collect();
+            let mods: Modifiers = Modifiers::from_param(params.get(1).copied().unwrap_or(1));
+            let len: usize = end + 1;
+            let code: Option<KeyCode> = match buf[end] {
+                b'~' if params[0] == 200 => return parse_paste(buf, len),
+                b'~' => tilde_key(params[0]),
+                b'Z' => Some(KeyCode::BackTab),
+                b => final_key(b),
+            };
+            match code {
+                Some(code) => Parse::Event(KeyEvent::Key(code, mods), len),
+                None => Parse::Invalid(len),
+            }
+        }
+
+        /// A private function that collects the text of a bracketed paste that starts at ```start```.
+        fn parse_paste(buf: &[u8], start: usize) -> Parse {
+            match buf[start..]
+                .windows(PASTE_END.len())
+                .position(|w| w == PASTE_END)
+            {
+                Some(i) => {
+                    let text: String = String::from_utf8_lossy(&buf[start..start + i]).into_owned();
+                    Parse::Event(KeyEvent::Paste(text), start + i + PASTE_END.len())
+                }
+                None => Parse::Incomplete,
+            }
+        }
+
+        /// Overwrites ```bytes``` with zeros. Volatile writes are used so that the compiler cannot
+        /// remove them even though the memory is not read again.
+        pub(crate) fn zero(bytes: &mut [u8]) {
+            for b in bytes.iter_mut() {
+                unsafe { std::ptr::write_volatile(b, 0) };
+            }
+            std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+        }
+
+        /// Overwrites the spare capacity of ```bytes``` with zeros, e.g. the bytes left behind
+        /// after the start of the vector was drained.
+        pub(crate) fn zero_spare(bytes: &mut Vec<u8>) {
+            for b in bytes.spare_capacity_mut() {
+                unsafe { std::ptr::write_volatile(b, std::mem::MaybeUninit::new(0)) };
+            }
+            std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+        }
+
+        /// A reader of stdin that reads from its file descriptor directly, without the buffer of
+        /// ```io::Stdin```, so that no copy of what was read is left behind in that buffer. This is
+        /// how ```prompt::Password``` reads secrets.
+        #[derive(Debug, Clone, Copy, Default)]
+        pub struct UnbufferedStdin;
+
+        impl Read for UnbufferedStdin {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                sys::read_stdin(buf)
+            }
+        }
+
+        /// Reads bytes from a terminal and decodes them into ```KeyEvent```s. Terminals send each
+        /// escape sequence in a single write, so an escape byte that arrives on its own is decoded
+        /// as the Esc key instead of waiting for the rest of a sequence. Bytes are overwritten
+        /// with zeros once they have been decoded, so that a secret typed at the keyboard does not
+        /// linger in memory.
+        #[derive(Debug)]
+        pub struct KeyReader<R> {
+            reader: R,
+            buf: Vec<u8>,
+        }
+
+        impl<R> Drop for KeyReader<R> {
+            fn drop(&mut self) {
+                zero(&mut self.buf);
+                zero_spare(&mut self.buf);
+            }
+        }
+
+        impl KeyReader<UnbufferedStdin> {
+            /// Creates a new KeyReader that reads from the file descriptor of stdin, without the
+            /// buffer of ```io::Stdin```, so that the only copy of the bytes read is the one that
+            /// is zeroed. See ```RawMode```.
+            pub fn stdin() -> Self {
+                Self::new(UnbufferedStdin)
+            }
+        }
+
+        impl<R: Read> KeyReader<R> {
+            /// Creates a new KeyReader that reads from ```reader```.
+            pub fn new(reader: R) -> Self {
+                Self {
+                    reader,
+                    buf: Vec::new(),
+                }
+            }
+
+            /// Blocks until the next key event is available and returns it. Returns an error of
+            /// kind ```UnexpectedEof``` once the reader is exhausted.
+            pub fn read_key(&mut self) -> io::Result<KeyEvent> {
+                loop {
+                    match parse(&self.buf) {
+                        Parse::Event(event, n) => {
+                            self.consume(n);
+                            return Ok(event);
+                        }
+                        Parse::Invalid(n) => {
+                            self.consume(n);
+                            continue;
+                        }
+                        Parse::Incomplete if self.buf == [ESC] => {
+                            self.consume(1);
+                            return Ok(KeyEvent::key(KeyCode::Esc));
+                        }
+                        Parse::Incomplete => {}
+                    }
+                    let mut chunk: [u8; 256] = [0; 256];
+                    let read: io::Result<usize> = self.reader.read(&mut chunk);
+                    let n: usize = match read {
+                        Ok(n) => n,
+                        Err(e) => {
+                            zero(&mut chunk);
+                            return Err(e);
+                        }
+                    };
+                    if n == 0 {
+                        if self.buf.is_empty() {
+                            return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+                        }
+                        // decode whatever is left one byte at a time
+                        let b: u8 = self.buf[0];
+                        self.consume(1);
+                        return Ok(parse_byte(b));
+                    }
+                    self.push(&chunk[..n]);
+                    zero(&mut chunk);
+                }
+            }
+
+            /// A private function that removes the first ```n``` bytes of the buffer and zeros the
+            /// bytes left behind at its end.
+            fn consume(&mut self, n: usize) {
+                self.buf.drain(..n);
+                zero_spare(&mut self.buf);
+            }
+
+            /// A private function that appends ```bytes``` to the buffer. If the buffer has to grow,
+            /// then the old allocation is zeroed before it is freed.
+            fn push(&mut self, bytes: &[u8]) {
+                if self.buf.capacity() - self.buf.len() < bytes.len() {
+                    let capacity: usize =
+                        usize::max(self.buf.capacity() * 2, self.buf.len() + bytes.len());
+                    let mut grown: Vec<u8> = Vec::with_capacity(capacity.max(256));
+                    grown.extend_from_slice(&self.buf);
+                    let mut old: Vec<u8> = std::mem::replace(&mut self.buf, grown);
+                    zero(&mut old);
+                }
+                self.buf.extend_from_slice(bytes);
+            }
+        }
+
+        impl<R: Read> Iterator for KeyReader<R> {
+            type Item = io::Result<KeyEvent>;
+
+            /// Returns the next key event, or ```None``` once the reader is exhausted.
+            fn next(&mut self) -> Option<Self::Item> {
+                match self.read_key() {
+                    Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => None,
+                    result => Some(result),
+                }
+            }
+        }
+
+        #[cfg(test)]
+        mod tests {
+
+            use super::*;
+
+            fn key(code: KeyCode, mods: Modifiers) -> KeyEvent {
+                KeyEvent::Key(code, mods)
+            }
+
+            fn read_all(input: &[u8]) -> Vec<KeyEvent> {
+                KeyReader::new(input).map(Result::unwrap).collect()
+            }
+
+            #[test]
+            fn decoded_bytes_are_zeroed() {
+                let mut reader: KeyReader<&[u8]> = KeyReader::new(&b"ab\x1b[A"[..]);
+                assert_eq!(
+                    reader.read_key().unwrap(),
+                    key(KeyCode::Char('a'), Modifiers::NONE)
+                );
+                assert_eq!(reader.buf, b"b\x1b[A");
+                assert!(reader
+                    .buf
+                    .spare_capacity_mut()
+                    .iter()
+                    .all(|b| unsafe { b.assume_init() } == 0));
+                let mut bytes: Vec<u8> = vec![1, 2, 3];
+                bytes.truncate(1);
+                zero_spare(&mut bytes);
+                zero(&mut bytes);
+                assert_eq!(bytes, [0]);
+            }
+
+            #[test]
+            fn parses_plain_and_control_bytes() {
+                let none: Modifiers = Modifiers::NONE;
+                assert_eq!(parse(b"a"), Parse::Event(key(KeyCode::Char('a'), none), 1));
+                assert_eq!(parse(b"\r"), Parse::Event(key(KeyCode::Enter, none), 1));
+                assert_eq!(
+                    parse(b"\x7f"),
+                    Parse::Event(key(KeyCode::Backspace, none), 1)
+                );
+                assert_eq!(
+                    parse(b"\x03"),
+                    Parse::Event(key(KeyCode::Char('c'), Modifiers::CTRL), 1)
+                );
+            }
+
+            #[test]
+            fn parses_utf8() {
+                let bytes: &[u8] = "é".as_bytes();
+                assert_eq!(
+                    parse(bytes),
+                    Parse::Event(key(KeyCode::Char('é'), Modifiers::NONE), 2)
+                );
+                assert_eq!(parse(&bytes[..1]), Parse::Incomplete);
+                assert_eq!(parse(b"\xff"), Parse::Invalid(1));
+            }
+
+            #[test]
+            fn parses_escape_sequences() {
+                let none: Modifiers = Modifiers::NONE;
+                assert_eq!(parse(b"\x1b[A"), Parse::Event(key(KeyCode::Up, none), 3));
+                assert_eq!(parse(b"\x1bOP"), Parse::Event(key(KeyCode::F(1), none), 3));
+                assert_eq!(
+                    parse(b"\x1b[3~"),
+                    Parse::Event(key(KeyCode::Delete, none), 4)
+                );
+                assert_eq!(
+                    parse(b"\x1b[Z"),
+                    Parse::Event(key(KeyCode::BackTab, none), 3)
+                );
+                assert_eq!(
+                    parse(b"\x1b[1;5C"),
+                    Parse::Event(key(KeyCode::Right, Modifiers::CTRL), 6)
+                );
+                assert_eq!(
+                    parse(b"\x1bx"),
+                    Parse::Event(key(KeyCode::Char('x'), Modifiers::ALT), 2)
+                );
+                assert_eq!(parse(b"\x1b[99~"), Parse::Invalid(5));
+            }
+
+            #[test]
+            fn waits_for_the_rest_of_a_sequence() {
+                assert_eq!(parse(b""), Parse::Incomplete);
+                assert_eq!(parse(b"\x1b"), Parse::Incomplete);
+                assert_eq!(parse(b"\x1b["), Parse::Incomplete);
+                assert_eq!(parse(b"\x1b[1;5"), Parse::Incomplete);
+                assert_eq!(parse(b"\x1b[200~abc"), Parse::Incomplete);
+            }
+
+            #[test]
+            fn parses_bracketed_paste() {
+                assert_eq!(
+                    parse(b"\x1b[200~a\nb\x1b[201~x"),
+                    Parse::Event(KeyEvent::Paste(String::from("a\nb")), 15)
+                );
+            }
+
+            #[test]
+            fn an_escape_before_an_undecodable_key_is_esc() {
+                let esc: KeyEvent = KeyEvent::key(KeyCode::Esc);
+                assert_eq!(parse(b"\x1b\x1b"), Parse::Event(esc.clone(), 1));
+                assert_eq!(parse(b"\x1b\xff"), Parse::Event(esc.clone(), 1));
+                assert_eq!(read_all(b"\x1b\x1b"), [esc.clone(), esc.clone()]);
+                assert_eq!(read_all(b"\x1b"), [esc]);
+                assert_eq!(read_all(b"\x1b\x1b[A"), [key(KeyCode::Up, Modifiers::ALT)]);
+            }
+
+            #[test]
+            fn a_run_of_escapes_is_one_esc_per_byte() {
+                let esc: KeyEvent = KeyEvent::key(KeyCode::Esc);
+                assert_eq!(parse(b"\x1b\x1b\x1b"), Parse::Event(esc.clone(), 1));
+                assert_eq!(
+                    read_all(b"\x1b\x1b\x1b"),
+                    [esc.clone(), esc.clone(), esc.clone()]
+                );
+                assert_eq!(
+                    read_all(b"\x1b\x1b\x1b[A"),
+                    [esc, key(KeyCode::Up, Modifiers::ALT)]
+                );
+            }
+
+            #[test]
+            fn reads_several_events_from_one_chunk() {
+                assert_eq!(
+                    read_all(b"a\x1b[Bb"),
+                    [
+                        KeyEvent::key(KeyCode::Char('a')),
+                        KeyEvent::key(KeyCode::Down),
+                        KeyEvent::key(KeyCode::Char('b')),
+                    ]
+                );
+            }
+        }
+    }
+
+    /// A private module containing the bindings to the C library used by this module. The
+    /// layouts of ```Termios``` and ```SigAction``` and the constants match the ones used by glibc
+    /// and musl on Linux for x86_64 and aarch64 only. Other architectures use different values, so
+    /// they get the fallback module instead.
+    #[cfg(all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    mod sys {
+
+        use std::cell::UnsafeCell;
+        use std::io;
+        use std::mem::MaybeUninit;
+        use std::os::raw::{c_int, c_ulong, c_void};
+        use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+        use std::sync::Once;
+
+        const SIGINT: c_int = 2;
+        const SIGTERM: c_int = 15;
+        const SIGWINCH: c_int = 28;
+        const SIG_IGN: usize = 1;
+        const SIG_UNBLOCK: c_int = 1;
+        const SA_SIGINFO: c_int = 0x4;
+        const SA_RESTART: c_int = 0x1000_0000;
+        const F_SETFL: c_int = 4;
+        const O_NONBLOCK: c_int = 0o4000;
+        const O_CLOEXEC: c_int = 0o2000000;
+        const TCSANOW: c_int = 0;
+        const TIOCGWINSZ: c_ulong = 0x5413;
+        const OPOST: u32 = 0o1;
+        const ECHO: u32 = 0o10;
+
+        /// The terminal attributes of a file descriptor.
+        #[repr(C)]
+        #[derive(Debug, Clone, Copy)]
+        pub struct Termios {
+            c_iflag: u32,
+            c_oflag: u32,
+            c_cflag: u32,
+            c_lflag: u32,
+            c_line: u8,
+            c_cc: [u8; 32],
+            c_ispeed: u32,
+            c_ospeed: u32,
+        }
+
+        /// The disposition of a signal as used by ```sigaction()```.
+        #[repr(C)]
+        #[derive(Clone, Copy)]
+        struct SigAction {
+            sa_handler: usize,
+            sa_mask: [u64; 16],
+            sa_flags: c_int,
+            sa_restorer: usize,
+        }
+
+        impl SigAction {
+            /// Returns a disposition that calls ```handler``` with no signals masked.
+            fn new(handler: extern "C" fn(c_int)) -> Self {
+                Self {
+                    sa_handler: handler as usize,
+                    sa_mask: [0; 16],
+                    sa_flags: SA_RESTART,
+                    sa_restorer: 0,
+                }
+            }
+
+            /// Returns a disposition that calls ```handler``` with the details of the signal, which
+            /// it can pass on to a handler that was installed with SA_SIGINFO.
+            fn with_info(handler: extern "C" fn(c_int, *mut c_void, *mut c_void)) -> Self {
+                Self {
+                    sa_handler: handler as usize,
+                    sa_mask: [0; 16],
+                    sa_flags: SA_RESTART | SA_SIGINFO,
+                    sa_restorer: 0,
+                }
+            }
+        }
+
+        /// The size of a terminal as returned by the TIOCGWINSZ ioctl.
+        #[repr(C)]
+        #[derive(Debug, Default)]
+        struct Winsize {
+            ws_row: u16,
+            ws_col: u16,
+            ws_xpixel: u16,
+            ws_ypixel: u16,
+        }
+
+        extern "C" {
+            fn sigaction(signum: c_int, act: *const SigAction, oldact: *mut SigAction) -> c_int;
+            fn raise(sig: c_int) -> c_int;
+            fn pthread_sigmask(how: c_int, set: *const [u64; 16], oldset: *mut [u64; 16]) -> c_int;
+            fn write(fd: c_int, buf: *const c_void, count: usize) -> isize;
+            fn read(fd: c_int, buf: *mut c_void, count: usize) -> isize;
+            fn pipe2(fds: *mut c_int, flags: c_int) -> c_int;
+            fn fcntl(fd: c_int, cmd: c_int, ...) -> c_int;
+            fn __errno_location() -> *mut c_int;
+            fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
+            fn tcgetattr(fd: c_int, termios: *mut Termios) -> c_int;
+            fn tcsetattr(fd: c_int, optional_actions: c_int, termios: *const Termios) -> c_int;
+            fn cfmakeraw(termios: *mut Termios);
+        }
+
+        /// The attributes of stdin before they were first changed by this crate, which are
+        /// restored by the signal handler. A mutex cannot be used because it is not safe to lock
+        /// one from a signal handler, so the value is only written while ```ORIGINAL_SET``` is false.
+        struct Original(UnsafeCell<MaybeUninit<Termios>>);
+
+        unsafe impl Sync for Original {}
+
+        static ORIGINAL: Original = Original(UnsafeCell::new(MaybeUninit::uninit()));
+        static ORIGINAL_SET: AtomicBool = AtomicBool::new(false);
+        static ORIGINAL_LOCK: AtomicBool = AtomicBool::new(false);
+
+        /// Writes ```bytes``` to stdout without any buffering or locking.
+        pub fn write_stdout(bytes: &[u8]) {
+            unsafe {
+                write(1, bytes.as_ptr() as *const c_void, bytes.len());
+            }
+        }
+
+        /// Reads from the file descriptor of stdin, without the buffer of ```io::Stdin```.
+        pub fn read_stdin(buf: &mut [u8]) -> io::Result<usize> {
+            let n: isize = unsafe { read(0, buf.as_mut_ptr() as *mut c_void, buf.len()) };
+            if n < 0 {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(n as usize)
+            }
+        }
+
+        /// The dispositions of SIGINT and SIGTERM before ```install_signal_handlers()``` replaced
+        /// them, and of SIGWINCH before ```watch_resize()``` replaced it. They are only written
+        /// before the handlers are installed, so the handlers can read them without locking.
+        struct Previous(UnsafeCell<[MaybeUninit<SigAction>; 3]>);
+
+        unsafe impl Sync for Previous {}
+
+        static PREVIOUS: Previous = Previous(UnsafeCell::new([MaybeUninit::uninit(); 3]));
+
+        /// A private function that returns the slot in ```PREVIOUS``` of a signal.
+        fn previous_slot(sig: c_int) -> Option<usize> {
+            match sig {
+                SIGINT => Some(0),
+                SIGTERM => Some(1),
+                SIGWINCH => Some(2),
+                _ => None,
+            }
+        }
+
+        /// Calls ```handler``` when the program receives SIGINT or SIGTERM, saving the previous
+        /// dispositions so that ```raise_previous()``` can hand the signal on to them. A signal
+        /// that is ignored, e.g. by a program started with ```nohup```, stays ignored. This must
+        /// only be called once.
+        pub fn install_signal_handlers(handler: extern "C" fn(c_int)) {
+            let action: SigAction = SigAction::new(handler);
+            for sig in [SIGINT, SIGTERM] {
+                let slot: usize = previous_slot(sig).unwrap();
+                unsafe {
+                    let previous: *mut SigAction = (*PREVIOUS.0.get())[slot].as_mut_ptr();
+                    if sigaction(sig, std::ptr::null(), previous) != 0 {
+                        continue;
+                    }
+                    if (*previous).sa_handler != SIG_IGN {
+                        sigaction(sig, &action, std::ptr::null_mut());
+                    }
+                }
+            }
+        }
+
+        /// Puts back the disposition ```sig``` had before ```install_signal_handlers()``` and
+        /// raises it again, so that the program's own handler runs or the default action
+        /// terminates the program. The signal is unblocked first, so it is delivered before
+        /// ```raise()``` returns rather than after the current handler returns. If the program's
+        /// handler returns, then ```handler``` is installed again so that it also runs for the
+        /// next signal.
+        pub fn raise_previous(sig: c_int, handler: extern "C" fn(c_int)) {
+            if let Some(slot) = previous_slot(sig) {
+                let mut set: [u64; 16] = [0; 16];
+                set[0] = 1 << (sig - 1);
+                unsafe {
+                    sigaction(
+                        sig,
+                        (*PREVIOUS.0.get())[slot].as_ptr(),
+                        std::ptr::null_mut(),
+                    );
+                    pthread_sigmask(SIG_UNBLOCK, &set, std::ptr::null_mut());
+                    raise(sig);
+                    sigaction(sig, &SigAction::new(handler), std::ptr::null_mut());
+                }
+            }
+        }
+
+        /// The write end of the pipe that the SIGWINCH handler writes to, or -1.
+        static RESIZE_PIPE: AtomicI32 = AtomicI32::new(-1);
+
+        /// Returns the size of the terminal attached to stdout, stderr, or stdin, in that order.
+        pub fn window_size() -> Option<super::Size> {
+            for fd in [1, 2, 0] {
+                let mut ws: Winsize = Winsize::default();
+                if unsafe { ioctl(fd, TIOCGWINSZ, &mut ws as *mut Winsize) } == 0 && ws.ws_col > 0 {
+                    return Some(super::Size {
+                        cols: ws.ws_col,
+                        rows: ws.ws_row,
+                    });
+                }
+            }
+            None
+        }
+
+        /// Ensures that the SIGWINCH handler is only installed once.
+        static RESIZE_HANDLER: Once = Once::new();
+
+        /// A signal handler that wakes up the thread reading from the resize pipe and then calls
+        /// the SIGWINCH handler the program had before, if any. The write end of the pipe is
+        /// non-blocking, so once the reader falls behind and the pipe is full the byte is simply
+        /// dropped, which is fine because one byte is enough to wake it up. ```errno``` is saved
+        /// because the interrupted code may be about to read it.
+        extern "C" fn on_resize(sig: c_int, info: *mut c_void, context: *mut c_void) {
+            unsafe {
+                let errno: c_int = *__errno_location();
+                let fd: c_int = RESIZE_PIPE.load(Ordering::SeqCst);
+                if fd >= 0 {
+                    write(fd, [0u8].as_ptr() as *const c_void, 1);
+                }
+                *__errno_location() = errno;
+                let previous: &SigAction = (*PREVIOUS.0.get())[2].assume_init_ref();
+                // SIG_DFL and SIG_IGN both ignore SIGWINCH
+                if previous.sa_handler > SIG_IGN {
+                    if previous.sa_flags & SA_SIGINFO != 0 {
+                        let handler: extern "C" fn(c_int, *mut c_void, *mut c_void) =
+                            std::mem::transmute(previous.sa_handler);
+                        handler(sig, info, context);
+                    } else {
+                        let handler: extern "C" fn(c_int) =
+                            std::mem::transmute(previous.sa_handler);
+                        handler(sig);
+                    }
+                }
+            }
+        }
+
+        /// The read end of the resize pipe. Iterating over it blocks until the terminal is resized.
+        pub struct ResizeSignals(c_int);
+
+        impl Iterator for ResizeSignals {
+            type Item = ();
+
+            fn next(&mut self) -> Option<()> {
+                let mut byte: u8 = 0;
+                loop {
+                    match unsafe { read(self.0, &mut byte as *mut u8 as *mut c_void, 1) } {
+                        1 => return Some(()),
+                        -1 if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted => {}
+                        _ => return None,
+                    }
+                }
+            }
+        }
+
+        /// Installs a SIGWINCH handler and returns the pipe it writes a byte to on every signal.
+        /// The handler is chained to the one the program had before. Both ends of the pipe are
+        /// closed on exec, and only the write end is non-blocking, so that the handler never
+        /// blocks while reading still waits for the next signal. Returns ```None``` if the pipe
+        /// cannot be created or if the handler has already been installed by an earlier call.
+        pub fn watch_resize() -> Option<ResizeSignals> {
+            let mut signals: Option<ResizeSignals> = None;
+            RESIZE_HANDLER.call_once(|| {
+                let mut fds: [c_int; 2] = [-1; 2];
+                if unsafe { pipe2(fds.as_mut_ptr(), O_CLOEXEC) } != 0 {
+                    return;
+                }
+                unsafe {
+                    fcntl(fds[1], F_SETFL, O_NONBLOCK);
+                    let previous: *mut SigAction = (*PREVIOUS.0.get())[2].as_mut_ptr();
+                    if sigaction(SIGWINCH, std::ptr::null(), previous) != 0 {
+                        // treat it like SIG_DFL, so that the handler never calls anything
+                        previous.write(SigAction {
+                            sa_handler: 0,
+                            sa_mask: [0; 16],
+                            sa_flags: 0,
+                            sa_restorer: 0,
+                        });
+                    }
+                }
+                RESIZE_PIPE.store(fds[1], Ordering::SeqCst);
+                let action: SigAction = SigAction::with_info(on_resize);
+                unsafe { sigaction(SIGWINCH, &action, std::ptr::null_mut()) };
+                signals = Some(ResizeSignals(fds[0]));
+            });
+            signals
+        }
+
+        /// Returns the terminal attributes of stdin.
+        pub fn get_attr() -> io::Result<Termios> {
+            let mut termios: MaybeUninit<Termios> = MaybeUninit::uninit();
+            if unsafe { tcgetattr(0, termios.as_mut_ptr()) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(unsafe { termios.assume_init() })
+        }
+
+        /// Sets the terminal attributes of stdin.
+        pub fn set_attr(termios: &Termios) -> io::Result<()> {
+            if unsafe { tcsetattr(0, TCSANOW, termios) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        }
+
+        /// Turns off line buffering, echo, signals, and input processing, but leaves output
+        /// processing on so that "\n" still moves the cursor to the beginning of the next line.
+        pub fn make_raw(termios: &mut Termios) {
+            unsafe { cfmakeraw(termios) };
+            termios.c_oflag |= OPOST;
+        }
+
+        /// Turns echo on or off.
+        pub fn set_echo(termios: &mut Termios, echo: bool) {
+            if echo {
+                termios.c_lflag |= ECHO;
+            } else {
+                termios.c_lflag &= !ECHO;
+            }
+        }
+
+        /// Saves ```termios``` as the original attributes of stdin unless they have already been
+        /// saved. Returns ```true``` if they were saved by this call.
+        pub fn save_original(termios: &Termios) -> bool {
+            if ORIGINAL_LOCK.swap(true, Ordering::SeqCst) {
+                return false;
+            }
+            unsafe { (*ORIGINAL.0.get()).write(*termios) };
+            ORIGINAL_SET.store(true, Ordering::SeqCst);
+            true
+        }
+
+        /// Forgets the original attributes of stdin once they have been restored.
+        pub fn clear_original() {
+            ORIGINAL_SET.store(false, Ordering::SeqCst);
+            ORIGINAL_LOCK.store(false, Ordering::SeqCst);
+        }
+
+        /// Restores the original attributes of stdin, if any have been saved. This only uses
+        /// async-signal-safe calls.
+        pub fn restore_original() {
+            if ORIGINAL_SET.load(Ordering::SeqCst) {
+                unsafe { tcsetattr(0, TCSANOW, (*ORIGINAL.0.get()).as_ptr()) };
+            }
+        }
+    }
+
+    /// A private module containing fallbacks for platforms without a C library binding.
+    #[cfg(not(all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    )))]
+    mod sys {
+
+        use std::io;
+        use std::io::Write;
+        use std::os::raw::c_int;
+
+        #[derive(Debug, Clone, Copy)]
+        pub struct Termios;
+
+        fn unsupported() -> io::Error {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                "terminal attributes are only supported on Linux on x86_64 and aarch64",
+            )
+        }
+
+        pub fn write_stdout(bytes: &[u8]) {
+            let _ = std::io::stdout().write_all(bytes);
+        }
+
+        pub fn read_stdin(buf: &mut [u8]) -> io::Result<usize> {
+            io::Read::read(&mut io::stdin(), buf)
+        }
+
+        pub fn install_signal_handlers(_: extern "C" fn(c_int)) {}
+
+        pub fn raise_previous(_: c_int, _: extern "C" fn(c_int)) {}
+
+        pub fn window_size() -> Option<super::Size> {
+            None
+        }
+
+        pub fn watch_resize() -> Option<std::iter::Empty<()>> {
+            None
+        }
+
+        pub fn get_attr() -> io::Result<Termios> {
+            Err(unsupported())
+        }
+
+        pub fn set_attr(_: &Termios) -> io::Result<()> {
+            Err(unsupported())
+        }
+
+        pub fn make_raw(_: &mut Termios) {}
+
+        pub fn set_echo(_: &mut Termios, _: bool) {}
+
+        pub fn save_original(_: &Termios) -> bool {
+            false
+        }
+
+        pub fn clear_original() {}
+
+        pub fn restore_original() {}
+    }
+
+    #[cfg(test)]
+    mod tests {
+
+        use super::*;
+
+        #[test]
+        fn cursor_commands_use_one_based_positions() {
+            assert_eq!(Cursor::Up(3).to_string(), "\x1b[3A");
+            assert_eq!(Cursor::Down(3).to_string(), "\x1b[3B");
+            assert_eq!(Cursor::Right(3).to_string(), "\x1b[3C");
+            assert_eq!(Cursor::Left(3).to_string(), "\x1b[3D");
+            assert_eq!(Cursor::NextLine(2).to_string(), "\x1b[2E");
+            assert_eq!(Cursor::PrevLine(2).to_string(), "\x1b[2F");
+            assert_eq!(Cursor::ToColumn(0).to_string(), "\x1b[1G");
+            assert_eq!(Cursor::ToColumn(9).to_string(), "\x1b[10G");
+            assert_eq!(Cursor::ToColumn(u16::MAX).to_string(), "\x1b[65536G");
+            assert_eq!(Cursor::To(0, 0).to_string(), "\x1b[1;1H");
+            assert_eq!(Cursor::To(4, 9).to_string(), "\x1b[5;10H");
+            assert_eq!(Cursor::Save.to_string(), "\x1b7");
+            assert_eq!(Cursor::Restore.to_string(), "\x1b8");
+            assert_eq!(Cursor::Hide.to_string(), "\x1b[?25l");
+            assert_eq!(Cursor::Show.to_string(), "\x1b[?25h");
+        }
+
+        #[test]
+        fn moving_the_cursor_by_zero_does_nothing() {
+            for cursor in [
+                Cursor::Up(0),
+                Cursor::Down(0),
+                Cursor::Left(0),
+                Cursor::Right(0),
+                Cursor::NextLine(0),
+                Cursor::PrevLine(0),
+            ] {
+                assert_eq!(cursor.to_string(), "", "{:?}", cursor);
+            }
+        }
+
+        #[test]
+        fn panics_restore_on_the_main_thread_and_threads_with_guards() {
+            let main: thread::Thread = thread::Builder::new()
+                .name(String::from("main"))
+                .spawn(thread::current)
+                .unwrap()
+                .join()
+                .unwrap();
+            assert!(restores_on_panic(&main));
+            thread::spawn(|| {
+                assert!(!restores_on_panic(&thread::current()));
+                let guard: CursorGuard = CursorGuard::hide();
+                let nested: CursorGuard = CursorGuard::hide();
+                assert!(restores_on_panic(&thread::current()));
+                drop(guard);
+                assert!(restores_on_panic(&thread::current()));
+                drop(nested);
+                assert!(!restores_on_panic(&thread::current()));
+            })
+            .join()
+            .unwrap();
+        }
+
+        #[test]
+        fn erase_commands() {
+            assert_eq!(Erase::LineToEnd.to_string(), "\x1b[0K");
+            assert_eq!(Erase::LineToStart.to_string(), "\x1b[1K");
+            assert_eq!(Erase::Line.to_string(), "\x1b[2K");
+            assert_eq!(Erase::ScreenToEnd.to_string(), "\x1b[0J");
+            assert_eq!(Erase::ScreenToStart.to_string(), "\x1b[1J");
+            assert_eq!(Erase::Screen.to_string(), "\x1b[2J");
+            assert_eq!(Erase::Scrollback.to_string(), "\x1b[3J");
+        }
+
+        #[test]
+        fn screen_commands() {
+            assert_eq!(Screen::ScrollRegion(0, 23).to_string(), "\x1b[1;24r");
+            assert_eq!(Screen::ResetScrollRegion.to_string(), "\x1b[r");
+            assert_eq!(Screen::ScrollUp(2).to_string(), "\x1b[2S");
+            assert_eq!(Screen::ScrollDown(2).to_string(), "\x1b[2T");
+            assert_eq!(Screen::ScrollUp(0).to_string(), "");
+            assert_eq!(Screen::ScrollDown(0).to_string(), "");
+            assert_eq!(Screen::EnterAlternate.to_string(), "\x1b[?1049h");
+            assert_eq!(Screen::LeaveAlternate.to_string(), "\x1b[?1049l");
+            assert_eq!(Screen::EnableBracketedPaste.to_string(), "\x1b[?2004h");
+            assert_eq!(Screen::DisableBracketedPaste.to_string(), "\x1b[?2004l");
+        }
+    }
+}
+
+/// A module of interactive prompts that ask the user a question on the command line and wait
+/// for an answer. Every prompt has an ```ask()``` function that uses stdin and stdout, and an
+/// ```ask_with()``` function that uses any reader and writer, which is useful for testing. When
+/// stdin is not a terminal, e.g. when a program is run from a script, prompts do not wait for
+/// an answer and use their default instead.
+///
+/// Prompts can also be answered from a script of pre-recorded answers, e.g. in integration
+/// tests or automated pipelines. See ```prompt::set_answers()```.
+pub mod prompt {
+
+    use crate::readline;
+    use crate::term::input::{
+        self, EchoOff, KeyCode, KeyEvent, KeyReader, Modifiers, RawMode, UnbufferedStdin,
+    };
+    use crate::term::{self, Cursor, CursorGuard, Erase, Size};
+    use crate::text::{self, Style};
+    use std::collections::VecDeque;
+    use std::fmt;
+    use std::fmt::Display;
+    use std::fs;
+    use std::io::{self, BufRead, IsTerminal, Read, Write};
+    use std::path::Path;
+    use std::str::FromStr;
+    use std::sync::Mutex;
+
+    /// The environment variable that holds scripted answers, one per line.
+    pub const ANSWERS_VAR: &str = "CLI_TOOLS_ANSWERS";
+
+    /// The environment variable that holds the path of a file of scripted answers, one per line.
+    pub const ANSWERS_FILE_VAR: &str = "CLI_TOOLS_ANSWERS_FILE";
+
+    /// A private struct that holds the scripted answers, if any, and whether the environment
+    /// variables have been checked for them.
+    struct Script {
+        answers: Option<VecDeque<String>>,
+        env_checked: bool,
+    }
+
+    static SCRIPT: Mutex<Script> = Mutex::new(Script {
+        answers: None,
+        env_checked: false,
+    });
+
+    /// A private function that locks the script, ignoring a panic in another thread.
+    fn script() -> std::sync::MutexGuard<'static, Script> {
+        SCRIPT.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// A private function that reads the lines of a file of answers.
+    fn read_answers(path: &Path) -> io::Result<VecDeque<String>> {
+        Ok(fs::read_to_string(path)?
+            .lines()
+            .map(String::from)
+            .collect())
+    }
+
+    /// Answers all prompts from ```answers```, in order, instead of asking the user. Each
+    /// prompt takes the next answer and parses it the same way as a line typed by the user, and
+    /// an empty answer chooses the default. Menus also accept the text of an item. Once the
+    /// answers run out, prompts return an error of kind ```UnexpectedEof```, and an answer that
+    /// is not valid returns an error of kind ```InvalidData```.
+    ///
+    /// If no answers are set, then they are read from the file named by ```ANSWERS_FILE_VAR```
+    /// or from ```ANSWERS_VAR``` the first time a prompt is asked, if either is set.
+    ///
+    /// ```
+    /// use cli_tools::prompt::{self, Confirm};
+    ///
+    /// prompt::set_answers(["yes"]);
+    /// assert!(Confirm::new("Deploy to production?").ask().unwrap());
+    /// assert!(Confirm::new("Are you sure?").ask().is_err());
+    /// prompt::clear_answers();
+    /// ```
+    pub fn set_answers<I, S>(answers: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut script = script();
+        script.answers = Some(answers.into_iter().map(Into::into).collect());
+        script.env_checked = true;
+    }
+
+    /// Answers all prompts from the lines of the file at ```path```, like ```set_answers()```.
+    pub fn load_answers(path: &Path) -> io::Result<()> {
+        let answers: VecDeque<String> = read_answers(path)?;
+        set_answers(answers);
+        Ok(())
+    }
+
+    /// Removes the scripted answers so that prompts ask the user again. The environment
+    /// variables are not checked again.
+    pub fn clear_answers() {
+        let mut script = script();
+        script.answers = None;
+        script.env_checked = true;
+    }
+
+    /// Returns the number of scripted answers left, or ```None``` if prompts are not scripted.
+    pub fn remaining_answers() -> Option<usize> {
+        let mut script = script();
+        load_env_answers(&mut script).ok()?;
+        script.answers.as_ref().map(VecDeque::len)
+    }
+
+    /// A private function that loads the answers from the environment variables the first time
+    /// it is called, unless answers were set before.
+    fn load_env_answers(script: &mut Script) -> io::Result<()> {
+        if std::mem::replace(&mut script.env_checked, true) {
+            return Ok(());
+        }
+        if let Some(path) = std::env::var_os(ANSWERS_FILE_VAR) {
+            load_answers_from(script, Path::new(&path))?;
+        } else if let Ok(answers) = std::env::var(ANSWERS_VAR) {
+            script.answers = Some(answers.lines().map(String::from).collect());
+        }
+        Ok(())
+    }
+
+    /// A private function that replaces the answers of ```script``` with the lines of the file at
+    /// ```path```. The answers are left unchanged if the file cannot be read.
+    fn load_answers_from(script: &mut Script, path: &Path) -> io::Result<()> {
+        script.answers = Some(read_answers(path)?);
+        Ok(())
+    }
+
+    /// Takes the next scripted answer for ```question```. Returns ```None``` if prompts are not
+    /// scripted, in which case the user should be asked, and an error of kind
+    /// ```UnexpectedEof``` if the answers have run out. This is useful for custom prompts
+    /// that should be scripted like the ones in this module.
+    pub fn next_answer(question: &str) -> Option<io::Result<String>> {
+        let mut script = script();
+        if let Err(e) = load_env_answers(&mut script) {
+            return Some(Err(e));
+        }
+        let answers: &mut VecDeque<String> = script.answers.as_mut()?;
+        Some(answers.pop_front().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("no scripted answer for \"{}\"", question),
+            )
+        }))
+    }
+
+    /// A private function that returns the error used when a scripted answer is not valid.
+    fn invalid_answer(question: &str, answer: &str, msg: &str) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "scripted answer \"{}\" for \"{}\" is not valid: {}",
+                answer, question, msg
+            ),
+        )
+    }
+
+    /// A private function that returns the error used when a prompt cannot be answered because
+    /// stdin is not a terminal and the prompt has no default.
+    fn no_default(question: &str) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "cannot ask \"{}\" because stdin is not a terminal and there is no default",
+                question
+            ),
+        )
+    }
+
+    /// A private function that prints an error message below a prompt.
+    fn write_error<W: Write>(output: &mut W, msg: &str) -> io::Result<()> {
+        writeln!(output, "{}\u{2718} {}{}", Style::Red, msg, Style::Regular)
+    }
+
+    /// A private function that reads a line from ```input``` without the line ending. Returns
+    /// ```None``` at the end of the input.
+    fn read_line<R: BufRead>(input: &mut R) -> io::Result<Option<String>> {
+        let mut line: String = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let len: usize = line.trim_end_matches(['\r', '\n']).len();
+        line.truncate(len);
+        Ok(Some(line))
+    }
+
+    /// A private function that lets the user edit a single line of text in raw mode, starting
+    /// with ```initial```. The line is printed after ```prompt```, which may contain styles.
+    /// Supports moving the cursor with the arrow keys, Home and End, and deleting with
+    /// Backspace and Delete. Returns ```None``` if Ctrl-D is pressed on an empty line and an
+    /// error of kind ```Interrupted``` if Ctrl-C is pressed.
+    fn edit_line(prompt: &str, initial: &str) -> io::Result<Option<String>> {
+        let _raw: RawMode = RawMode::enable()?;
+        let mut keys = KeyReader::stdin();
+        let mut stdout = io::stdout();
+        let mut chars: Vec<char> = initial.chars().collect();
+        let mut cursor: usize = chars.len();
+        let mut scroll: usize = 0;
+        loop {
+            let line: String = chars.iter().collect();
+            let cols: usize = term::size().map_or(80, |size| size.cols as usize);
+            readline::render_line(&mut stdout, prompt, &chars, cursor, &mut scroll, cols)?;
+            match keys.read_key()? {
+                KeyEvent::Key(KeyCode::Enter, _) => {
+                    writeln!(stdout)?;
+                    return Ok(Some(line));
+                }
+                KeyEvent::Key(KeyCode::Char('c'), Modifiers::CTRL) => {
+                    writeln!(stdout)?;
+                    return Err(io::Error::from(io::ErrorKind::Interrupted));
+                }
+                KeyEvent::Key(KeyCode::Char('d'), Modifiers::CTRL) if chars.is_empty() => {
+                    writeln!(stdout)?;
+                    return Ok(None);
+                }
+                KeyEvent::Key(KeyCode::Left, _) => cursor = cursor.saturating_sub(1),
+                KeyEvent::Key(KeyCode::Right, _) => cursor = usize::min(cursor + 1, chars.len()),
+                KeyEvent::Key(KeyCode::Home, _) => cursor = 0,
+                KeyEvent::Key(KeyCode::End, _) => cursor = chars.len(),
+                KeyEvent::Key(KeyCode::Backspace, _) if cursor > 0 => {
+                    cursor -= 1;
+                    chars.remove(cursor);
+                }
+                KeyEvent::Key(KeyCode::Delete, _) if cursor < chars.len() => {
+                    chars.remove(cursor);
+                }
+                KeyEvent::Key(KeyCode::Char(c), mods) if !mods.contains(Modifiers::CTRL) => {
+                    chars.insert(cursor, c);
+                    cursor += 1;
+                }
+                KeyEvent::Paste(text) => {
+                    for c in text.chars().filter(|c| !c.is_control()) {
+                        chars.insert(cursor, c);
+                        cursor += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// A yes/no question, e.g. "Delete all files? [y/N]". The answers y, yes, n and no are
+    /// accepted in any case, and pressing enter chooses the default, if any. Any other answer
+    /// prints an error and asks the question again.
+    #[derive(Debug, Clone)]
+    pub struct Confirm {
+        question: String,
+        default: Option<bool>,
+        style: Style,
+    }
+
+    impl Confirm {
+        /// Creates a new question without a default answer, printed in ```Style::Bold```.
+        pub fn new(question: &str) -> Self {
+            Self {
+                question: String::from(question),
+                default: None,
+                style: Style::Bold,
+            }
+        }
+
+        /// Returns the question.
+        pub fn question(&self) -> &str {
+            &self.question
+        }
+
+        /// Returns the answer chosen when the user presses enter, if any.
+        pub fn default_value(&self) -> Option<bool> {
+            self.default
+        }
+
+        /// Sets the answer chosen when the user presses enter or when stdin is not a terminal.
+        /// Pass ```None``` to require an explicit answer.
+        pub fn set_default(&mut self, default: Option<bool>) {
+            self.default = default;
+        }
+
+        /// Returns the text::Style used to print the question.
+        pub fn style(&self) -> Style {
+            self.style
+        }
+
+        /// Sets the text::Style used to print the question.
+        pub fn set_style(&mut self, style: Style) {
+            self.style = style;
+        }
+
+        /// A private function that returns the hint printed after the question.
+        fn hint(&self) -> &'static str {
+            match self.default {
+                Some(true) => "[Y/n]",
+                Some(false) => "[y/N]",
+                None => "[y/n]",
+            }
+        }
+
+        /// A private function that converts an answer into a bool, or ```None``` if it is invalid.
+        fn parse(&self, answer: &str) -> Option<bool> {
+            match answer.trim().to_lowercase().as_str() {
+                "y" | "yes" => Some(true),
+                "n" | "no" => Some(false),
+                "" => self.default,
+                _ => None,
+            }
+        }
+
+        /// Asks the question on stdout and waits for an answer on stdin. If stdin is not a
+        /// terminal, then the default answer is printed and returned without waiting, or an
+        /// error of kind ```InvalidInput``` is returned if there is no default. If prompts are
+        /// scripted, then the next scripted answer is printed and returned instead.
+        pub fn ask(&self) -> io::Result<bool> {
+            let scripted: Option<String> = next_answer(&self.question).transpose()?;
+            if scripted.is_none() && io::stdin().is_terminal() {
+                return self.ask_with(io::stdin().lock(), io::stdout());
+            }
+            self.answer_with(scripted, io::stdout())
+        }
+
+        /// A private function that answers the question without waiting for the user, with the
+        /// ```scripted``` answer if there is one and with the default otherwise, and prints the
+        /// answer on ```output```.
+        fn answer_with<W: Write>(
+            &self,
+            scripted: Option<String>,
+            mut output: W,
+        ) -> io::Result<bool> {
+            let answer: bool = match scripted {
+                Some(answer) => self.parse(&answer).ok_or_else(|| {
+                    invalid_answer(&self.question, &answer, "Please answer yes or no.")
+                })?,
+                None => self.default.ok_or_else(|| no_default(&self.question))?,
+            };
+            writeln!(
+                output,
+                "{}{}{} {} {}",
+                self.style,
+                self.question,
+                Style::Regular,
+                self.hint(),
+                if answer { "y" } else { "n" }
+            )?;
+            Ok(answer)
+        }
+
+        /// Asks the question on ```output``` and reads answers from ```input``` until a valid one is
+        /// given. Returns the default answer at the end of the input, or an error of kind
+        /// ```UnexpectedEof``` if there is no default.
+        pub fn ask_with<R: BufRead, W: Write>(
+            &self,
+            mut input: R,
+            mut output: W,
+        ) -> io::Result<bool> {
+            loop {
+                write!(
+                    output,
+                    "{}{}{} {} ",
+                    self.style,
+                    self.question,
+                    Style::Regular,
+                    self.hint()
+                )?;
+                output.flush()?;
+                let line: String = match read_line(&mut input)? {
+                    Some(line) => line,
+                    None => {
+                        writeln!(output)?;
+                        return self
+                            .default
+                            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof));
+                    }
+                };
+                match self.parse(&line) {
+                    Some(answer) => return Ok(answer),
+                    None => write_error(&mut output, "Please answer yes or no.")?,
+                }
+            }
+        }
+    }
+
+    /// A function that checks a value entered into an ```Input``` and returns an error message
+    /// if it is not acceptable.
+    pub type Validator<T> = Box<dyn Fn(&T) -> Result<(), String>>;
+
+    /// A question that is answered with a line of text, which is parsed into a ```T``` using
+    /// ```FromStr```, e.g. "Port [8080] ". If the text cannot be parsed or is rejected by one of
+    /// the validators, the error is printed and the question is asked again.
+    pub struct Input<T> {
+        prompt: String,
+        default: Option<T>,
+        initial_text: String,
+        style: Style,
+        validators: Vec<Validator<T>>,
+    }
+
+    impl<T> fmt::Debug for Input<T>
+    where
+        T: fmt::Debug,
+    {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Input")
+                .field("prompt", &self.prompt)
+                .field("default", &self.default)
+                .field("initial_text", &self.initial_text)
+                .field("style", &self.style)
+                .field("validators", &self.validators.len())
+                .finish()
+        }
+    }
+
+    impl<T> Input<T>
+    where
+        T: FromStr + Clone + Display,
+        T::Err: Display,
+    {
+        /// Creates a new question without a default value or initial text, printed in ```Style::Bold```.
+        pub fn new(prompt: &str) -> Self {
+            Self {
+                prompt: String::from(prompt),
+                default: None,
+                initial_text: String::new(),
+                style: Style::Bold,
+                validators: Vec::new(),
+            }
+        }
+
+        /// Returns the question.
+        pub fn prompt(&self) -> &str {
+            &self.prompt
+        }
+
+        /// Returns the value chosen when the user enters an empty line, if any.
+        pub fn default_value(&self) -> Option<&T> {
+            self.default.as_ref()
+        }
+
+        /// Sets the value chosen when the user enters an empty line or when stdin is not a
+        /// terminal. The default is printed in brackets after the question. Pass ```None``` to
+        /// require an explicit answer.
+        pub fn set_default(&mut self, default: Option<T>) {
+            self.default = default;
+        }
+
+        /// Returns the text the answer starts with.
+        pub fn initial_text(&self) -> &str {
+            &self.initial_text
+        }
+
+        /// Sets the text the answer starts with, which the user can edit before pressing enter.
+        /// Unlike the default, the initial text is part of the answer. When the answer is read
+        /// with ```Input::ask_with()```, an empty line accepts the initial text unchanged.
+        pub fn set_initial_text(&mut self, text: &str) {
+            self.initial_text = String::from(text);
+        }
+
+        /// Returns the text::Style used to print the question.
+        pub fn style(&self) -> Style {
+            self.style
+        }
+
+        /// Sets the text::Style used to print the question.
+        pub fn set_style(&mut self, style: Style) {
+            self.style = style;
+        }
+
+        /// Adds a function that checks each parsed value. Validators are called in the order
+        /// they were added and the first error message returned is printed below the question.
+        pub fn add_validator<F>(&mut self, validator: F)
+        where
+            F: Fn(&T) -> Result<(), String> + 'static,
+        {
+            self.validators.push(Box::new(validator));
+        }
+
+        /// A private function that returns the question followed by the default, if any.
+        fn render_prompt(&self) -> String {
+            match &self.default {
+                Some(default) => format!(
+                    "{}{}{} [{}] ",
+                    self.style,
+                    self.prompt,
+                    Style::Regular,
+                    default
+                ),
+                None => format!("{}{}{} ", self.style, self.prompt, Style::Regular),
+            }
+        }
+
+        /// A private function that turns an answer into a value or an error message.
+        fn parse(&self, answer: &str) -> Result<T, String> {
+            let value: T = match (answer.trim(), &self.default) {
+                ("", Some(default)) => default.clone(),
+                ("", None) => return Err(String::from("Please enter a value.")),
+                (answer, _) => answer.parse::<T>().map_err(|e| e.to_string())?,
+            };
+            for validator in &self.validators {
+                validator(&value)?;
+            }
+            Ok(value)
+        }
+
+        /// Asks the question on stdout and lets the user edit the answer in raw mode, starting
+        /// from the initial text. If stdin is not a terminal, then the default is printed and
+        /// returned without waiting, or an error of kind ```InvalidInput``` is returned if there
+        /// is no default. Pressing Ctrl-C returns an error of kind ```Interrupted```. If prompts
+        /// are scripted, then the next scripted answer is parsed and returned instead, where an
+        /// empty answer accepts the initial text.
+        pub fn ask(&self) -> io::Result<T> {
+            let scripted: Option<String> = next_answer(&self.prompt).transpose()?;
+            if scripted.is_some() || !io::stdin().is_terminal() {
+                return self.answer_with(scripted, io::stdout());
+            }
+            loop {
+                let answer: String = edit_line(&self.render_prompt(), &self.initial_text)?
+                    .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+                match self.parse(&answer) {
+                    Ok(value) => return Ok(value),
+                    Err(msg) => write_error(&mut io::stdout(), &msg)?,
+                }
+            }
+        }
+
+        /// A private function that answers the question without waiting for the user, with the
+        /// ```scripted``` answer if there is one and with the default otherwise, and prints the
+        /// answer on ```output```. An empty scripted answer accepts the initial text.
+        fn answer_with<W: Write>(&self, scripted: Option<String>, mut output: W) -> io::Result<T> {
+            let value: T = match scripted {
+                Some(answer) if answer.is_empty() && !self.initial_text.is_empty() => self
+                    .parse(&self.initial_text)
+                    .map_err(|msg| invalid_answer(&self.prompt, &answer, &msg))?,
+                Some(answer) => self
+                    .parse(&answer)
+                    .map_err(|msg| invalid_answer(&self.prompt, &answer, &msg))?,
+                None => self
+                    .default
+                    .clone()
+                    .ok_or_else(|| no_default(&self.prompt))?,
+            };
+            writeln!(output, "{}{}", self.render_prompt(), value)?;
+            Ok(value)
+        }
+
+        /// Asks the question on ```output``` and reads answers from ```input``` until a valid one is
+        /// given. Returns an error of kind ```UnexpectedEof``` at the end of the input.
+        pub fn ask_with<R: BufRead, W: Write>(&self, mut input: R, mut output: W) -> io::Result<T> {
+            loop {
+                write!(output, "{}{}", self.render_prompt(), self.initial_text)?;
+                output.flush()?;
+                let mut answer: String = read_line(&mut input)?
+                    .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+                if answer.is_empty() {
+                    answer = self.initial_text.clone();
+                }
+                match self.parse(&answer) {
+                    Ok(value) => return Ok(value),
+                    Err(msg) => write_error(&mut output, &msg)?,
+                }
+            }
+        }
+    }
+
+    /// A private struct that holds the cursor and scroll position of a list shown in raw mode,
+    /// and redraws the list in place.
+    struct Menu {
+        len: usize,
+        cursor: usize,
+        offset: usize,
+        height: usize,
+        cols: usize,
+        up: usize,
+    }
+
+    impl Menu {
+        /// Creates a menu of ```len``` items with the cursor on ```cursor```. The menu shows as many
+        /// items as fit below the header in the terminal, but no more than ```max_height```.
+        fn new(len: usize, cursor: usize, max_height: Option<usize>) -> Self {
+            let size: Option<Size> = term::size();
+            let rows: usize = size.map_or(24, |size| size.rows as usize);
+            let height: usize = rows.saturating_sub(2).min(max_height.unwrap_or(usize::MAX));
+            let mut menu: Menu = Self {
+                len,
+                cursor: 0,
+                offset: 0,
+                height: height.max(1),
+                cols: size.map_or(80, |size| size.cols as usize),
+                up: 0,
+            };
+            menu.move_to(cursor);
+            menu
+        }
+
+        /// Changes the number of items and moves the cursor back to the first item.
+        fn set_len(&mut self, len: usize) {
+            self.len = len;
+            self.offset = 0;
+            self.move_to(0);
+        }
+
+        /// Moves the cursor to ```index``` and scrolls the list so that the cursor is visible.
+        fn move_to(&mut self, index: usize) {
+            self.cursor = index.min(self.len.saturating_sub(1));
+            if self.cursor < self.offset {
+                self.offset = self.cursor;
+            } else if self.cursor >= self.offset + self.height {
+                self.offset = self.cursor + 1 - self.height;
+            }
+        }
+
+        /// Moves the cursor if ```key``` is a navigation key. The arrow keys and j/k move by one
+        /// item and wrap around at either end, PageUp and PageDown move by a page, and Home and
+        /// End jump to the first and last item. Returns ```false``` if the key was not used.
+        fn navigate(&mut self, key: &KeyEvent, vi_keys: bool) -> bool {
+            let last: usize = self.len.saturating_sub(1);
+            match key {
+                KeyEvent::Key(KeyCode::Up, _) => self.move_to(if self.cursor == 0 {
+                    last
+                } else {
+                    self.cursor - 1
+                }),
+                KeyEvent::Key(KeyCode::Down, _) => self.move_to(if self.cursor == last {
+                    0
+                } else {
+                    self.cursor + 1
+                }),
+                KeyEvent::Key(KeyCode::Char('k'), Modifiers::NONE) if vi_keys => {
+                    self.move_to(if self.cursor == 0 {
+                        last
+                    } else {
+                        self.cursor - 1
+                    })
+                }
+                KeyEvent::Key(KeyCode::Char('j'), Modifiers::NONE) if vi_keys => {
+                    self.move_to(if self.cursor == last {
+                        0
+                    } else {
+                        self.cursor + 1
+                    })
+                }
+                KeyEvent::Key(KeyCode::PageUp, _) => {
+                    self.move_to(self.cursor.saturating_sub(self.height))
+                }
+                KeyEvent::Key(KeyCode::PageDown, _) => self.move_to(self.cursor + self.height),
+                KeyEvent::Key(KeyCode::Home, _) => self.move_to(0),
+                KeyEvent::Key(KeyCode::End, _) => self.move_to(last),
+                _ => return false,
+            }
+            true
+        }
+
+        /// Erases the previous drawing, then draws ```header``` followed by the visible items.
+        /// ```item``` renders the item at an index, given whether the cursor is on it and how
+        /// many columns are available.
+        fn draw<W, F>(&mut self, output: &mut W, header: &str, mut item: F) -> io::Result<()>
+        where
+            W: Write,
+            F: FnMut(usize, bool, usize) -> String,
+        {
+            self.clear(output)?;
+            writeln!(output, "{}", header)?;
+            let end: usize = usize::min(self.offset + self.height, self.len);
+            for index in self.offset..end {
+                let selected: bool = index == self.cursor;
+                let line: String = item(index, selected, self.cols.saturating_sub(3));
+                if selected {
+                    writeln!(output, "{}> {}{}", Style::Highlight, line, Style::Regular)?;
+                } else {
+                    writeln!(output, "  {}", line)?;
+                }
+            }
+            self.up = 1 + end - self.offset;
+            output.flush()
+        }
+
+        /// Moves the cursor back up to ```column``` of the header, e.g. to show where the user is
+        /// typing.
+        fn park<W: Write>(&mut self, output: &mut W, column: usize) -> io::Result<()> {
+            let up: u16 = self.up.min(u16::MAX as usize) as u16;
+            let column: u16 = column.min(u16::MAX as usize) as u16;
+            write!(output, "{}{}", Cursor::Up(up), Cursor::ToColumn(column))?;
+            self.up = 0;
+            output.flush()
+        }
+
+        /// Erases the previous drawing, leaving the cursor where it started.
+        fn clear<W: Write>(&mut self, output: &mut W) -> io::Result<()> {
+            let up: u16 = self.up.min(u16::MAX as usize) as u16;
+            write!(output, "\r{}{}", Cursor::Up(up), Erase::ScreenToEnd)?;
+            self.up = 0;
+            Ok(())
+        }
+    }
+
+    /// A private function that prints a numbered list of ```items``` for prompts that read
+    /// their answer from a line of text.
+    fn write_numbered<W: Write>(output: &mut W, items: &[String]) -> io::Result<()> {
+        let digits: usize = items.len().to_string().len();
+        for (i, item) in items.iter().enumerate() {
+            writeln!(output, "  {:>digits$}) {}", i + 1, item)?;
+        }
+        Ok(())
+    }
+
+    /// A private function that parses a 1-based item number into an index.
+    fn parse_number(answer: &str, len: usize) -> Result<usize, String> {
+        match answer.trim().parse::<usize>() {
+            Ok(n) if n >= 1 && n <= len => Ok(n - 1),
+            _ => Err(format!("Please enter a number between 1 and {}.", len)),
+        }
+    }
+
+    /// A private function that finds an item by its text or its 1-based number.
+    fn find_item(items: &[String], answer: &str) -> Result<usize, String> {
+        let answer: &str = answer.trim();
+        match items.iter().position(|item| item == answer) {
+            Some(index) => Ok(index),
+            None => parse_number(answer, items.len()),
+        }
+    }
+
+    /// A private function that returns the error used when a menu has no items.
+    fn no_items(prompt: &str) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "cannot ask \"{}\" because there are no items to choose from",
+                prompt
+            ),
+        )
+    }
+
+    /// A menu that lets the user choose one item from a list. The item under the cursor is
+    /// printed in ```Style::Highlight``` and the cursor is moved with the arrow keys or j/k.
+    /// Pressing enter chooses the item under the cursor. Lists that do not fit in the terminal
+    /// scroll.
+    #[derive(Debug, Clone)]
+    pub struct Select {
+        prompt: String,
+        items: Vec<String>,
+        default: Option<usize>,
+        max_height: Option<usize>,
+        style: Style,
+    }
+
+    impl Select {
+        /// Creates a new menu without items or a default, printed in ```Style::Bold```.
+        pub fn new(prompt: &str) -> Self {
+            Self {
+                prompt: String::from(prompt),
+                items: Vec::new(),
+                default: None,
+                max_height: None,
+                style: Style::Bold,
+            }
+        }
+
+        /// Returns the question.
+        pub fn prompt(&self) -> &str {
+            &self.prompt
+        }
+
+        /// Adds an item to the end of the list.
+        pub fn add_item(&mut self, item: &str) {
+            self.items.push(String::from(item));
+        }
+
+        /// Returns the items.
+        pub fn items(&self) -> &[String] {
+            &self.items
+        }
+
+        /// Returns the index of the item chosen by default, if any.
+        pub fn default_value(&self) -> Option<usize> {
+            self.default
+        }
+
+        /// Sets the index of the item the cursor starts on, which is also chosen when stdin is
+        /// not a terminal. An index past the end of the list is ignored.
+        pub fn set_default(&mut self, default: Option<usize>) {
+            self.default = default;
+        }
+
+        /// Returns the maximum number of items shown at once, if any.
+        pub fn max_height(&self) -> Option<usize> {
+            self.max_height
+        }
+
+        /// Sets the maximum number of items shown at once. The list is always limited to the
+        /// height of the terminal. Pass ```None``` to use the whole terminal.
+        pub fn set_max_height(&mut self, max_height: Option<usize>) {
+            self.max_height = max_height;
+        }
+
+        /// Returns the text::Style used to print the question.
+        pub fn style(&self) -> Style {
+            self.style
+        }
+
+        /// Sets the text::Style used to print the question.
+        pub fn set_style(&mut self, style: Style) {
+            self.style = style;
+        }
+
+        /// A private function that returns the default if it is in range.
+        fn valid_default(&self) -> Option<usize> {
+            self.default.filter(|&i| i < self.items.len())
+        }
+
+        /// A private function that chooses an item without waiting for the user, the one with the
+        /// text or number of the ```scripted``` answer if there is one and the default otherwise,
+        /// and prints it on ```output```. An empty scripted answer chooses the default.
+        fn answer_with<W: Write>(
+            &self,
+            scripted: Option<String>,
+            mut output: W,
+        ) -> io::Result<usize> {
+            let index: usize = match scripted {
+                Some(answer) if answer.is_empty() => self.valid_default().ok_or_else(|| {
+                    invalid_answer(&self.prompt, &answer, "Please choose an item.")
+                })?,
+                Some(answer) => find_item(&self.items, &answer)
+                    .map_err(|msg| invalid_answer(&self.prompt, &answer, &msg))?,
+                None => self
+                    .valid_default()
+                    .ok_or_else(|| no_default(&self.prompt))?,
+            };
+            writeln!(
+                output,
+                "{}{}{} {}",
+                self.style,
+                self.prompt,
+                Style::Regular,
+                self.items[index]
+            )?;
+            Ok(index)
+        }
+
+        /// Shows the menu on stdout and returns the index of the chosen item. If stdin is not a
+        /// terminal, then the default is printed and returned without waiting, or an error of
+        /// kind ```InvalidInput``` is returned if there is no default. Pressing Esc or Ctrl-C
+        /// returns an error of kind ```Interrupted```. If prompts are scripted, then the item
+        /// with the text or the number of the next scripted answer is chosen instead.
+        pub fn ask(&self) -> io::Result<usize> {
+            if self.items.is_empty() {
+                return Err(no_items(&self.prompt));
+            }
+            let mut stdout = io::stdout();
+            let scripted: Option<String> = next_answer(&self.prompt).transpose()?;
+            if scripted.is_some() || !io::stdin().is_terminal() {
+                return self.answer_with(scripted, stdout);
+            }
+            let _raw: RawMode = RawMode::enable()?;
+            let _cursor: CursorGuard = CursorGuard::hide();
+            let mut keys = KeyReader::stdin();
+            let mut menu: Menu = Menu::new(
+                self.items.len(),
+                self.valid_default().unwrap_or(0),
+                self.max_height,
+            );
+            let header: String = format!("{}{}{}", self.style, self.prompt, Style::Regular);
+            loop {
+                menu.draw(&mut stdout, &header, |i, _, width| {
+                    text::truncate(&self.items[i], width)
+                })?;
+                let key: KeyEvent = keys.read_key()?;
+                if menu.navigate(&key, true) {
+                    continue;
+                }
+                match key {
+                    KeyEvent::Key(KeyCode::Enter, _) => {
+                        menu.clear(&mut stdout)?;
+                        writeln!(stdout, "{} {}", header, self.items[menu.cursor])?;
+                        return Ok(menu.cursor);
+                    }
+                    KeyEvent::Key(KeyCode::Esc, _)
+                    | KeyEvent::Key(KeyCode::Char('c'), Modifiers::CTRL) => {
+                        menu.clear(&mut stdout)?;
+                        return Err(io::Error::from(io::ErrorKind::Interrupted));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        /// Prints the question and a numbered list of items on ```output``` and reads item numbers
+        /// from ```input``` until a valid one is given. An empty line chooses the default, if any.
+        /// Returns an error of kind ```UnexpectedEof``` at the end of the input.
+        pub fn ask_with<R: BufRead, W: Write>(
+            &self,
+            mut input: R,
+            mut output: W,
+        ) -> io::Result<usize> {
+            if self.items.is_empty() {
+                return Err(no_items(&self.prompt));
+            }
+            writeln!(output, "{}{}{}", self.style, self.prompt, Style::Regular)?;
+            write_numbered(&mut output, &self.items)?;
+            loop {
+                match self.valid_default() {
+                    Some(default) => write!(output, "Choice [{}]: ", default + 1)?,
+                    None => write!(output, "Choice: ")?,
+                }
+                output.flush()?;
+                let answer: String = read_line(&mut input)?
+                    .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+                let parsed = match (answer.trim(), self.valid_default()) {
+                    ("", Some(default)) => Ok(default),
+                    (answer, _) => parse_number(answer, self.items.len()),
+                };
+                match parsed {
+                    Ok(index) => return Ok(index),
+                    Err(msg) => write_error(&mut output, &msg)?,
+                }
+            }
+        }
+    }
+
+    /// A menu that lets the user choose any number of items from a list. The cursor is moved
+    /// like in ```Select```, space checks or unchecks the item under the cursor, 'a' checks or
+    /// unchecks all items, and enter accepts the checked items.
+    #[derive(Debug, Clone)]
+    pub struct MultiSelect {
+        prompt: String,
+        items: Vec<String>,
+        checked: Vec<bool>,
+        max_height: Option<usize>,
+        style: Style,
+    }
+
+    impl MultiSelect {
+        /// Creates a new menu without items, printed in ```Style::Bold```.
+        pub fn new(prompt: &str) -> Self {
+            Self {
+                prompt: String::from(prompt),
+                items: Vec::new(),
+                checked: Vec::new(),
+                max_height: None,
+                style: Style::Bold,
+            }
+        }
+
+        /// Returns the question.
+        pub fn prompt(&self) -> &str {
+            &self.prompt
+        }
+
+        /// Adds an item to the end of the list. Items that are checked are chosen unless the
+        /// user unchecks them, and are chosen when stdin is not a terminal.
+        pub fn add_item(&mut self, item: &str, checked: bool) {
+            self.items.push(String::from(item));
+            self.checked.push(checked);
+        }
+
+        /// Returns the items.
+        pub fn items(&self) -> &[String] {
+            &self.items
+        }
+
+        /// Returns ```true``` if the item at ```index``` is checked by default.
+        pub fn is_checked(&self, index: usize) -> bool {
+            self.checked.get(index).copied().unwrap_or(false)
+        }
+
+        /// Checks or unchecks the item at ```index``` by default. An index past the end of the
+        /// list is ignored.
+        pub fn set_checked(&mut self, index: usize, checked: bool) {
+            if let Some(c) = self.checked.get_mut(index) {
+                *c = checked;
+            }
+        }
+
+        /// Returns the maximum number of items shown at once, if any.
+        pub fn max_height(&self) -> Option<usize> {
+            self.max_height
+        }
+
+        /// Sets the maximum number of items shown at once. The list is always limited to the
+        /// height of the terminal. Pass ```None``` to use the whole terminal.
+        pub fn set_max_height(&mut self, max_height: Option<usize>) {
+            self.max_height = max_height;
+        }
+
+        /// Returns the text::Style used to print the question.
+        pub fn style(&self) -> Style {
+            self.style
+        }
+
+        /// Sets the text::Style used to print the question.
+        pub fn set_style(&mut self, style: Style) {
+            self.style = style;
+        }
+
+        /// A private function that returns the indices of the checked items.
+        fn indices(checked: &[bool]) -> Vec<usize> {
+            (0..checked.len()).filter(|&i| checked[i]).collect()
+        }
+
+        /// A private function that prints the question followed by the chosen items.
+        fn write_answer<W: Write>(&self, output: &mut W, indices: &[usize]) -> io::Result<()> {
+            let chosen: Vec<&str> = indices.iter().map(|&i| self.items[i].as_str()).collect();
+            writeln!(
+                output,
+                "{}{}{} {}",
+                self.style,
+                self.prompt,
+                Style::Regular,
+                chosen.join(", ")
+            )
+        }
+
+        /// A private function that chooses items without waiting for the user, the ones with the
+        /// texts or numbers in the ```scripted``` answer if there is one and the items checked by
+        /// default otherwise, and prints them on ```output```. An empty scripted answer chooses
+        /// the items checked by default.
+        fn answer_with<W: Write>(
+            &self,
+            scripted: Option<String>,
+            mut output: W,
+        ) -> io::Result<Vec<usize>> {
+            let indices: Vec<usize> = match scripted {
+                Some(answer) if !answer.trim().is_empty() => {
+                    let mut checked: Vec<bool> = vec![false; self.items.len()];
+                    for item in answer.split(',') {
+                        let index: usize = find_item(&self.items, item)
+                            .map_err(|msg| invalid_answer(&self.prompt, &answer, &msg))?;
+                        checked[index] = true;
+                    }
+                    Self::indices(&checked)
+                }
+                _ => Self::indices(&self.checked),
+            };
+            self.write_answer(&mut output, &indices)?;
+            Ok(indices)
+        }
+
+        /// Shows the menu on stdout and returns the indices of the checked items in ascending
+        /// order. If stdin is not a terminal, then the items checked by default are printed and
+        /// returned without waiting. Pressing Esc or Ctrl-C returns an error of kind
+        /// ```Interrupted```. If prompts are scripted, then the items with the texts or numbers
+        /// in the next scripted answer, separated by commas, are chosen instead.
+        pub fn ask(&self) -> io::Result<Vec<usize>> {
+            if self.items.is_empty() {
+                return Err(no_items(&self.prompt));
+            }
+            let mut stdout = io::stdout();
+            let scripted: Option<String> = next_answer(&self.prompt).transpose()?;
+            if scripted.is_some() || !io::stdin().is_terminal() {
+                return self.answer_with(scripted, stdout);
+            }
+            let _raw: RawMode = RawMode::enable()?;
+            let _cursor: CursorGuard = CursorGuard::hide();
+            let mut keys = KeyReader::stdin();
+            let mut checked: Vec<bool> = self.checked.clone();
+            let mut menu: Menu = Menu::new(self.items.len(), 0, self.max_height);
+            let header: String = format!("{}{}{}", self.style, self.prompt, Style::Regular);
+            loop {
+                menu.draw(&mut stdout, &header, |i, _, width| {
+                    let mark: char = if checked[i] { 'x' } else { ' ' };
+                    format!(
+                        "[{}] {}",
+                        mark,
+                        text::truncate(&self.items[i], width.saturating_sub(4))
+                    )
+                })?;
+                let key: KeyEvent = keys.read_key()?;
+                if menu.navigate(&key, true) {
+                    continue;
+                }
+                match key {
+                    KeyEvent::Key(KeyCode::Char(' '), _) => {
+                        checked[menu.cursor] = !checked[menu.cursor]
+                    }
+                    KeyEvent::Key(KeyCode::Char('a'), Modifiers::NONE) => {
+                        let all: bool = checked.iter().all(|&c| c);
+                        checked.iter_mut().for_each(|c| *c = !all);
+                    }
+                    KeyEvent::Key(KeyCode::Enter, _) => {
+                        let indices: Vec<usize> = Self::indices(&checked);
+                        menu.clear(&mut stdout)?;
+                        self.write_answer(&mut stdout, &indices)?;
+                        return Ok(indices);
+                    }
+                    KeyEvent::Key(KeyCode::Esc, _)
+                    | KeyEvent::Key(KeyCode::Char('c'), Modifiers::CTRL) => {
+                        menu.clear(&mut stdout)?;
+                        return Err(io::Error::from(io::ErrorKind::Interrupted));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        /// Prints the question and a numbered list of items on ```output``` and reads item numbers
+        /// separated by commas or spaces from ```input``` until only valid ones are given. An
+        /// empty line chooses the items checked by default. Returns the indices in ascending
+        /// order, or an error of kind ```UnexpectedEof``` at the end of the input.
+        pub fn ask_with<R: BufRead, W: Write>(
+            &self,
+            mut input: R,
+            mut output: W,
+        ) -> io::Result<Vec<usize>> {
+            if self.items.is_empty() {
+                return Err(no_items(&self.prompt));
+            }
+            writeln!(output, "{}{}{}", self.style, self.prompt, Style::Regular)?;
+            write_numbered(&mut output, &self.items)?;
+            let defaults: Vec<usize> = Self::indices(&self.checked);
+            let hint: Vec<String> = defaults.iter().map(|i| (i + 1).to_string()).collect();
+            loop {
+                write!(output, "Choices [{}]: ", hint.join(","))?;
+                output.flush()?;
+                let answer: String = read_line(&mut input)?
+                    .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+                if answer.trim().is_empty() {
+                    return Ok(defaults);
+                }
+                let mut checked: Vec<bool> = vec![false; self.items.len()];
+                let parsed: Result<(), String> = answer
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|n| !n.is_empty())
+                    .try_for_each(|n| parse_number(n, self.items.len()).map(|i| checked[i] = true));
+                match parsed {
+                    Ok(()) => return Ok(Self::indices(&checked)),
+                    Err(msg) => write_error(&mut output, &msg)?,
+                }
+            }
+        }
+    }
+
+    const SCORE_MATCH: i64 = 16;
+    const BONUS_CONSECUTIVE: i64 = 16;
+    const BONUS_BOUNDARY: i64 = 12;
+    const BONUS_FIRST_CHAR: i64 = 8;
+    const PENALTY_GAP: i64 = 2;
+    const PENALTY_LEADING: i64 = 1;
+
+    /// A private function that compares a char of a pattern with a char of a candidate. The
+    /// comparison ignores case unless ```case_sensitive``` is ```true```.
+    fn chars_match(p: char, c: char, case_sensitive: bool) -> bool {
+        if case_sensitive || p == c {
+            return p == c;
+        }
+        if p.is_ascii() && c.is_ascii() {
+            return p.eq_ignore_ascii_case(&c);
+        }
+        c.to_lowercase().eq(p.to_lowercase())
+    }
+
+    /// A private function that returns ```true``` if the char at ```index``` starts a word, i.e.
+    /// it follows a separator or it is an uppercase letter that follows a lowercase one.
+    fn is_boundary(chars: &[char], index: usize) -> bool {
+        if index == 0 {
+            return true;
+        }
+        let (prev, c) = (chars[index - 1], chars[index]);
+        (!prev.is_alphanumeric() && c.is_alphanumeric())
+            || (prev.is_lowercase() && c.is_uppercase())
+    }
+
+    /// A private function that scores ```candidate``` against ```pattern``` with a subsequence
+    /// match. ```chars``` is a buffer that is reused between calls to avoid allocations. If
+    /// ```positions``` is given, it is filled with the indices of the matched chars.
+    ///
+    /// The first chars that complete the match are found going forward, then the match is
+    /// tightened by searching for the pattern backwards from there, which prefers matches that
+    /// are close together. Matched chars score more when they are consecutive or start a word,
+    /// and the gaps between matched chars and the chars before the match cost points.
+    fn fuzzy_score(
+        pattern: &[char],
+        candidate: &str,
+        case_sensitive: bool,
+        chars: &mut Vec<char>,
+        positions: Option<&mut Vec<usize>>,
+    ) -> Option<i64> {
+        chars.clear();
+        chars.extend(candidate.chars());
+        if pattern.is_empty() {
+            return Some(0);
+        }
+        let mut pi: usize = 0;
+        let mut end: usize = 0;
+        for (i, &c) in chars.iter().enumerate() {
+            if chars_match(pattern[pi], c, case_sensitive) {
+                pi += 1;
+                if pi == pattern.len() {
+                    end = i;
+                    break;
+                }
+            }
+        }
+        if pi < pattern.len() {
+            return None;
+        }
+        let mut start: usize = end;
+        let mut pi: usize = pattern.len();
+        for i in (0..=end).rev() {
+            if chars_match(pattern[pi - 1], chars[i], case_sensitive) {
+                pi -= 1;
+                if pi == 0 {
+                    start = i;
+                    break;
+                }
+            }
+        }
+        let mut positions: Option<&mut Vec<usize>> = positions;
+        if let Some(positions) = positions.as_deref_mut() {
+            positions.clear();
+        }
+        let mut score: i64 = -(start.min(16) as i64) * PENALTY_LEADING;
+        let mut pi: usize = 0;
+        let mut last: Option<usize> = None;
+        for i in start..=end {
+            if pi == pattern.len() {
+                break;
+            }
+            if !chars_match(pattern[pi], chars[i], case_sensitive) {
+                continue;
+            }
+            score += SCORE_MATCH;
+            if is_boundary(chars, i) {
+                score += BONUS_BOUNDARY;
+                if i == 0 {
+                    score += BONUS_FIRST_CHAR;
+                }
+            }
+            match last {
+                Some(last) if last + 1 == i => score += BONUS_CONSECUTIVE,
+                Some(last) => score -= (i - last - 1) as i64 * PENALTY_GAP,
+                None => {}
+            }
+            if let Some(positions) = positions.as_deref_mut() {
+                positions.push(i);
+            }
+            last = Some(i);
+            pi += 1;
+        }
+        Some(score)
+    }
+
+    /// Matches ```pattern``` against ```candidate``` as a subsequence, the way ```FuzzySelect```
+    /// filters its items. Case is ignored unless the pattern contains an uppercase letter.
+    /// Returns ```None``` if not every char of the pattern appears in the candidate in order,
+    /// otherwise a score, where higher is better, and the char indices of the matched chars.
+    pub fn fuzzy_match(pattern: &str, candidate: &str) -> Option<(i64, Vec<usize>)> {
+        let pattern: Vec<char> = pattern.chars().collect();
+        let case_sensitive: bool = pattern.iter().any(|c| c.is_uppercase());
+        let mut positions: Vec<usize> = Vec::new();
+        let score: i64 = fuzzy_score(
+            &pattern,
+            candidate,
+            case_sensitive,
+            &mut Vec::new(),
+            Some(&mut positions),
+        )?;
+        Some((score, positions))
+    }
+
+    /// A private struct that holds the items matching the current query of a ```FuzzySelect```,
+    /// sorted best first.
+    struct Matches {
+        query: Vec<char>,
+        found: Vec<(i64, usize)>,
+        chars: Vec<char>,
+    }
+
+    impl Matches {
+        /// Creates the matches for an empty query, which are all items in their original order.
+        fn new(len: usize) -> Self {
+            Self {
+                query: Vec::new(),
+                found: (0..len).map(|i| (0, i)).collect(),
+                chars: Vec::new(),
+            }
+        }
+
+        /// Updates the matches for a new query. If the new query extends the previous one, then
+        /// only the previous matches are searched, because an item that did not match cannot
+        /// match a longer query. Otherwise all items are searched again.
+        fn update(&mut self, items: &[String], query: &[char]) {
+            let case_sensitive: bool = query.iter().any(|c| c.is_uppercase());
+            let was_case_sensitive: bool = self.query.iter().any(|c| c.is_uppercase());
+            let extends: bool =
+                query.starts_with(&self.query) && case_sensitive == was_case_sensitive;
+            let mut found: Vec<(i64, usize)> = std::mem::take(&mut self.found);
+            if extends {
+                found.retain_mut(|(score, i)| {
+                    match fuzzy_score(query, &items[*i], case_sensitive, &mut self.chars, None) {
+                        Some(s) => {
+                            *score = s;
+                            true
+                        }
+                        None => false,
+                    }
+                });
+            } else {
+                found.clear();
+                for (i, item) in items.iter().enumerate() {
+                    if let Some(score) =
+                        fuzzy_score(query, item, case_sensitive, &mut self.chars, None)
+                    {
+                        found.push((score, i));
+                    }
+                }
+            }
+            found.sort_unstable_by_key(|&(score, i)| (std::cmp::Reverse(score), i));
+            self.found = found;
+            self.query = query.to_vec();
+        }
+
+        /// Returns the matched chars of an item for highlighting.
+        fn positions(&mut self, item: &str) -> Vec<usize> {
+            let case_sensitive: bool = self.query.iter().any(|c| c.is_uppercase());
+            let mut positions: Vec<usize> = Vec::new();
+            fuzzy_score(
+                &self.query,
+                item,
+                case_sensitive,
+                &mut self.chars,
+                Some(&mut positions),
+            );
+            positions
+        }
+    }
+
+    /// A private function that shortens ```item``` to ```width``` columns like ```text::truncate()```
+    /// and prints the chars at ```positions``` in ```style```. ```selected``` items are printed in
+    /// ```Style::Highlight```, which is restored after each matched char.
+    fn highlight(
+        item: &str,
+        positions: &[usize],
+        width: usize,
+        style: Style,
+        selected: bool,
+    ) -> String {
+        let truncated: bool = text::visible_width(item) > width;
+        let available: usize = if truncated {
+            width.saturating_sub(1)
+        } else {
+            width
+        };
+        let restore: String = if selected {
+            format!("{}{}", Style::Regular, Style::Highlight)
+        } else {
+            Style::Regular.to_string()
+        };
+        let mut result: String = String::new();
+        let mut used: usize = 0;
+        let mut positions = positions.iter().peekable();
+        for (i, c) in item.chars().enumerate() {
+            used += text::char_width(c);
+            if used > available {
+                break;
+            }
+            if positions.next_if_eq(&&i).is_some() {
+                result.push_str(&format!("{}{}{}", style, c, restore));
+            } else {
+                result.push(c);
+            }
+        }
+        if truncated && width > 0 {
+            result.push('\u{2026}');
+        }
+        result
+    }
+
+    /// A menu that lets the user choose one item by typing part of it. As the user types, the
+    /// list only shows the items that contain the typed chars in order, best matches first, with
+    /// the matched chars printed in the match style. Case is ignored unless the query contains
+    /// an uppercase letter. The cursor is moved with the arrow keys or Ctrl-N and Ctrl-P, and
+    /// pressing enter chooses the item under the cursor.
+    #[derive(Debug, Clone)]
+    pub struct FuzzySelect {
+        prompt: String,
+        items: Vec<String>,
+        default: Option<usize>,
+        max_height: Option<usize>,
+        style: Style,
+        match_style: Style,
+    }
+
+    impl FuzzySelect {
+        /// Creates a new menu without items or a default. The question is printed in
+        /// ```Style::Bold``` and matched chars in ```Style::Yellow```.
+        pub fn new(prompt: &str) -> Self {
+            Self {
+                prompt: String::from(prompt),
+                items: Vec::new(),
+                default: None,
+                max_height: None,
+                style: Style::Bold,
+                match_style: Style::Yellow,
+            }
+        }
+
+        /// Returns the question.
+        pub fn prompt(&self) -> &str {
+            &self.prompt
+        }
+
+        /// Adds an item to the end of the list.
+        pub fn add_item(&mut self, item: &str) {
+            self.items.push(String::from(item));
+        }
+
+        /// Returns the items.
+        pub fn items(&self) -> &[String] {
+            &self.items
+        }
+
+        /// Returns the index of the item chosen when stdin is not a terminal, if any.
+        pub fn default_value(&self) -> Option<usize> {
+            self.default
+        }
+
+        /// Sets the index of the item chosen when stdin is not a terminal. An index past the end
+        /// of the list is ignored.
+        pub fn set_default(&mut self, default: Option<usize>) {
+            self.default = default;
+        }
+
+        /// Returns the maximum number of items shown at once, if any.
+        pub fn max_height(&self) -> Option<usize> {
+            self.max_height
+        }
+
+        /// Sets the maximum number of items shown at once. The list is always limited to the
+        /// height of the terminal. Pass ```None``` to use the whole terminal.
+        pub fn set_max_height(&mut self, max_height: Option<usize>) {
+            self.max_height = max_height;
+        }
+
+        /// Returns the text::Style used to print the question.
+        pub fn style(&self) -> Style {
+            self.style
+        }
+
+        /// Sets the text::Style used to print the question.
+        pub fn set_style(&mut self, style: Style) {
+            self.style = style;
+        }
+
+        /// Returns the text::Style used to print the matched chars of each item.
+        pub fn match_style(&self) -> Style {
+            self.match_style
+        }
+
+        /// Sets the text::Style used to print the matched chars of each item.
+        pub fn set_match_style(&mut self, style: Style) {
+            self.match_style = style;
+        }
+
+        /// Shows the menu on stdout and returns the index of the chosen item in the list of
+        /// items. If stdin is not a terminal, then the default is printed and returned without
+        /// waiting, or an error of kind ```InvalidInput``` is returned if there is no default.
+        /// Backspace deletes the last char of the query and Ctrl-U clears it. Pressing Esc or
+        /// Ctrl-C returns an error of kind ```Interrupted```. If prompts are scripted, then the
+        /// item with the text of the next scripted answer, or else the best match for it, is
+        /// chosen instead.
+        pub fn ask(&self) -> io::Result<usize> {
+            if self.items.is_empty() {
+                return Err(no_items(&self.prompt));
+            }
+            let mut stdout = io::stdout();
+            let prompt: String = format!("{}{}{} ", self.style, self.prompt, Style::Regular);
+            let scripted: Option<String> = next_answer(&self.prompt).transpose()?;
+            if scripted.is_some() || !io::stdin().is_terminal() {
+                return self.answer_with(scripted, stdout);
+            }
+            let _raw: RawMode = RawMode::enable()?;
+            let mut keys = KeyReader::stdin();
+            let mut query: Vec<char> = Vec::new();
+            let mut matches: Matches = Matches::new(self.items.len());
+            let mut menu: Menu = Menu::new(self.items.len(), 0, self.max_height);
+            loop {
+                let typed: String = query.iter().collect();
+                let header: String = format!(
+                    "{}{}  {}{}/{}{}",
+                    prompt,
+                    typed,
+                    Style::Faint,
+                    matches.found.len(),
+                    self.items.len(),
+                    Style::Regular
+                );
+                menu.draw(&mut stdout, &header, |row, selected, width| {
+                    let item: &str = &self.items[matches.found[row].1];
+                    let positions: Vec<usize> = matches.positions(item);
+                    highlight(item, &positions, width, self.match_style, selected)
+                })?;
+                menu.park(
+                    &mut stdout,
+                    text::visible_width(&prompt) + text::visible_width(&typed),
+                )?;
+                let key: KeyEvent = keys.read_key()?;
+                if menu.navigate(&key, false) {
+                    continue;
+                }
+                match key {
+                    KeyEvent::Key(KeyCode::Enter, _) if !matches.found.is_empty() => {
+                        let index: usize = matches.found[menu.cursor].1;
+                        menu.clear(&mut stdout)?;
+                        writeln!(stdout, "{}{}", prompt, self.items[index])?;
+                        return Ok(index);
+                    }
+                    KeyEvent::Key(KeyCode::Esc, _)
+                    | KeyEvent::Key(KeyCode::Char('c'), Modifiers::CTRL) => {
+                        menu.clear(&mut stdout)?;
+                        return Err(io::Error::from(io::ErrorKind::Interrupted));
+                    }
+                    KeyEvent::Key(KeyCode::Char('p'), Modifiers::CTRL) => {
+                        menu.navigate(&KeyEvent::key(KeyCode::Up), false);
+                    }
+                    KeyEvent::Key(KeyCode::Char('n'), Modifiers::CTRL) => {
+                        menu.navigate(&KeyEvent::key(KeyCode::Down), false);
+                    }
+                    KeyEvent::Key(KeyCode::Char('u'), Modifiers::CTRL) => {
+                        query.clear();
+                        matches.update(&self.items, &query);
+                        menu.set_len(matches.found.len());
+                    }
+                    KeyEvent::Key(KeyCode::Backspace, _) if query.pop().is_some() => {
+                        matches.update(&self.items, &query);
+                        menu.set_len(matches.found.len());
+                    }
+                    KeyEvent::Key(KeyCode::Char(c), mods) if !mods.contains(Modifiers::CTRL) => {
+                        query.push(c);
+                        matches.update(&self.items, &query);
+                        menu.set_len(matches.found.len());
+                    }
+                    KeyEvent::Paste(pasted) => {
+                        query.extend(pasted.chars().filter(|c| !c.is_control()));
+                        matches.update(&self.items, &query);
+                        menu.set_len(matches.found.len());
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        /// A private function that chooses an item without waiting for the user, the one with the
+        /// text of the ```scripted``` answer, or else the best match for it, if there is one and
+        /// the default otherwise, and prints it on ```output```.
+        fn answer_with<W: Write>(
+            &self,
+            scripted: Option<String>,
+            mut output: W,
+        ) -> io::Result<usize> {
+            let index: usize = match scripted {
+                Some(answer) => match self.items.iter().position(|item| *item == answer) {
+                    Some(index) => index,
+                    None => {
+                        let mut matches: Matches = Matches::new(self.items.len());
+                        matches.update(&self.items, &answer.chars().collect::<Vec<char>>());
+                        let msg: String = format!("No items match \"{}\".", answer);
+                        matches
+                            .found
+                            .first()
+                            .map(|&(_, i)| i)
+                            .ok_or_else(|| invalid_answer(&self.prompt, &answer, &msg))?
+                    }
+                },
+                None => self
+                    .default
+                    .filter(|&i| i < self.items.len())
+                    .ok_or_else(|| no_default(&self.prompt))?,
+            };
+            writeln!(
+                output,
+                "{}{}{} {}",
+                self.style,
+                self.prompt,
+                Style::Regular,
+                self.items[index]
+            )?;
+            Ok(index)
+        }
+
+        /// Asks the question on ```output``` and reads a query from ```input```, then prints a
+        /// numbered list of the matching items, best first, and reads the number of one of them.
+        /// An empty line chooses the best match. If nothing matches, the query is read again.
+        /// Returns an error of kind ```UnexpectedEof``` at the end of the input.
+        pub fn ask_with<R: BufRead, W: Write>(
+            &self,
+            mut input: R,
+            mut output: W,
+        ) -> io::Result<usize> {
+            if self.items.is_empty() {
+                return Err(no_items(&self.prompt));
+            }
+            let mut matches: Matches = Matches::new(self.items.len());
+            loop {
+                write!(output, "{}{}{} ", self.style, self.prompt, Style::Regular)?;
+                output.flush()?;
+                let query: String = read_line(&mut input)?
+                    .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+                matches.update(&self.items, &query.chars().collect::<Vec<char>>());
+                if matches.found.is_empty() {
+                    write_error(&mut output, &format!("No items match \"{}\".", query))?;
+                    continue;
+                }
+                let limit: usize = self.max_height.unwrap_or(usize::MAX);
+                let found: Vec<String> = matches
+                    .found
+                    .iter()
+                    .take(limit)
+                    .map(|&(_, i)| self.items[i].clone())
+                    .collect();
+                write_numbered(&mut output, &found)?;
+                loop {
+                    write!(output, "Choice [1]: ")?;
+                    output.flush()?;
+                    let answer: String = read_line(&mut input)?
+                        .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+                    let parsed: Result<usize, String> = match answer.trim() {
+                        "" => Ok(0),
+                        answer => parse_number(answer, found.len()),
+                    };
+                    match parsed {
+                        Ok(row) => return Ok(matches.found[row].1),
+                        Err(msg) => write_error(&mut output, &msg)?,
+                    }
+                }
+            }
+        }
+    }
+
+    /// The longest secret in bytes that ```Password``` reads. Secrets are read into a buffer of
+    /// this size that never grows, because growing it would free copies of the secret without
+    /// zeroing them.
+    pub const MAX_SECRET_LEN: usize = 1024;
+
+    /// A private function that overwrites the entire allocation of ```bytes``` with zeros.
+    fn zero(bytes: &mut Vec<u8>) {
+        input::zero(bytes);
+        input::zero_spare(bytes);
+    }
+
+    /// A private function that returns the error used when a secret does not fit in
+    /// ```MAX_SECRET_LEN``` bytes.
+    fn secret_too_long() -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("the secret is longer than {} bytes", MAX_SECRET_LEN),
+        )
+    }
+
+    /// A private struct that holds the bytes of a secret while it is being read, zeroing them
+    /// when dropped. Its capacity is fixed at ```MAX_SECRET_LEN```.
+    struct SecretBuf(Vec<u8>);
+
+    impl SecretBuf {
+        /// Creates an empty buffer.
+        fn new() -> Self {
+            Self(Vec::with_capacity(MAX_SECRET_LEN))
+        }
+
+        /// Appends ```bytes```, or returns an error if they do not fit.
+        fn push(&mut self, bytes: &[u8]) -> io::Result<()> {
+            if self.0.len() + bytes.len() > MAX_SECRET_LEN {
+                return Err(secret_too_long());
+            }
+            self.0.extend_from_slice(bytes);
+            Ok(())
+        }
+
+        /// Appends ```c```, or returns an error if it does not fit.
+        fn push_char(&mut self, c: char) -> io::Result<()> {
+            self.push(c.encode_utf8(&mut [0; 4]).as_bytes())
+        }
+
+        /// Removes the last char. Returns ```false``` if the buffer was empty.
+        fn pop_char(&mut self) -> bool {
+            let s: &str = std::str::from_utf8(&self.0).unwrap_or_default();
+            match s.chars().next_back() {
+                Some(c) => {
+                    let len: usize = self.0.len() - c.len_utf8();
+                    self.0[len..].iter_mut().for_each(|b| *b = 0);
+                    self.0.truncate(len);
+                    true
+                }
+                None => false,
+            }
+        }
+
+        /// Turns the buffer into a secret without copying it. Returns an error if the bytes are
+        /// not valid UTF-8.
+        fn into_secret(mut self) -> io::Result<Secret> {
+            match String::from_utf8(std::mem::take(&mut self.0)) {
+                Ok(s) => Ok(Secret(s)),
+                Err(e) => {
+                    zero(&mut e.into_bytes());
+                    Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "the secret is not valid UTF-8",
+                    ))
+                }
+            }
+        }
+    }
+
+    impl Drop for SecretBuf {
+        fn drop(&mut self) {
+            zero(&mut self.0);
+        }
+    }
+
+    /// A private function that reads a line from ```input``` one byte at a time into a
+    /// ```SecretBuf```, without the line ending. Returns ```None``` at the end of the input and an
+    /// error if the line is longer than ```MAX_SECRET_LEN``` bytes. The rest of a line that is
+    /// too long is read and discarded so that the next read starts on the next line.
+    fn read_secret_line<R: Read>(input: &mut R) -> io::Result<Option<Secret>> {
+        let mut buf: SecretBuf = SecretBuf::new();
+        let mut byte: [u8; 1] = [0];
+        let mut read_any: bool = false;
+        let mut too_long: bool = false;
+        loop {
+            match input.read(&mut byte) {
+                Ok(0) if !read_any => return Ok(None),
+                Ok(0) => break,
+                Ok(_) if byte[0] == b'\n' => break,
+                Ok(_) => {
+                    read_any = true;
+                    too_long = too_long || buf.push(&byte).is_err();
+                    byte[0] = 0;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        if too_long {
+            return Err(secret_too_long());
+        }
+        if buf.0.last() == Some(&b'\r') {
+            buf.0.pop();
+            buf.0.spare_capacity_mut()[0].write(0);
+        }
+        buf.into_secret().map(Some)
+    }
+
+    /// A string that holds a secret, such as a password. Its memory is overwritten with zeros
+    /// when it is dropped and it is never printed by ```Debug```.
+    pub struct Secret(String);
+
+    impl Secret {
+        /// Creates a new secret that takes ownership of ```s```.
+        pub fn new(s: String) -> Self {
+            Self(s)
+        }
+
+        /// Returns the secret as a string slice. Avoid copying it into values that are not
+        /// zeroed when dropped.
+        pub fn expose(&self) -> &str {
+            &self.0
+        }
+
+        /// Returns the length of the secret in bytes.
+        pub fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        /// Returns ```true``` if the secret is empty.
+        pub fn is_empty(&self) -> bool {
+            self.0.is_empty()
+        }
+    }
+
+    impl PartialEq for Secret {
+        /// Compares two secrets in time that only depends on their lengths.
+        fn eq(&self, other: &Self) -> bool {
+            let (a, b) = (self.0.as_bytes(), other.0.as_bytes());
+            a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+        }
+    }
+
+    impl Eq for Secret {}
+
+    impl fmt::Debug for Secret {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("Secret(***)")
+        }
+    }
+
+    impl Drop for Secret {
+        /// Overwrites the entire allocation of the secret with zeros.
+        fn drop(&mut self) {
+            zero(unsafe { self.0.as_mut_vec() });
+        }
+    }
+
+    /// A private function that reads a line from stdin with echo turned off. If ```mask``` is set,
+    /// the line is read in raw mode instead and ```mask``` is printed for every char typed.
+    /// Pressing Ctrl-C returns an error of kind ```Interrupted``` in raw mode and raises
+    /// SIGINT otherwise, which restores the terminal before the program exits. Returns an error
+    /// if the secret is longer than ```MAX_SECRET_LEN``` bytes.
+    fn read_secret(prompt: &str, mask: Option<char>) -> io::Result<Secret> {
+        let mut stdout = io::stdout();
+        write!(stdout, "{}", prompt)?;
+        stdout.flush()?;
+        let mask: char = match mask {
+            Some(mask) => mask,
+            None => {
+                let _echo: EchoOff = EchoOff::enable()?;
+                let secret: Option<Secret> = read_secret_line(&mut UnbufferedStdin)?;
+                writeln!(stdout)?;
+                return secret.ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof));
+            }
+        };
+        let _raw: RawMode = RawMode::enable()?;
+        let mut keys: KeyReader<UnbufferedStdin> = KeyReader::new(UnbufferedStdin);
+        let mut secret: SecretBuf = SecretBuf::new();
+        loop {
+            match keys.read_key()? {
+                KeyEvent::Key(KeyCode::Enter, _) => {
+                    writeln!(stdout)?;
+                    return secret.into_secret();
+                }
+                KeyEvent::Key(KeyCode::Char('c'), Modifiers::CTRL) => {
+                    writeln!(stdout)?;
+                    return Err(io::Error::from(io::ErrorKind::Interrupted));
+                }
+                KeyEvent::Key(KeyCode::Backspace, _) if secret.pop_char() => {
+                    let width: u16 = text::char_width(mask).max(1) as u16;
+                    write!(stdout, "{}{}", Cursor::Left(width), Erase::LineToEnd)?;
+                }
+                KeyEvent::Key(KeyCode::Char(c), mods) if !mods.contains(Modifiers::CTRL) => {
+                    secret.push_char(c)?;
+                    write!(stdout, "{}", mask)?;
+                }
+                KeyEvent::Paste(pasted) => {
+                    let pasted: Secret = Secret(pasted);
+                    for c in pasted.expose().chars().filter(|c| !c.is_control()) {
+                        secret.push_char(c)?;
+                        write!(stdout, "{}", mask)?;
+                    }
+                }
+                _ => {}
+            }
+            stdout.flush()?;
+        }
+    }
+
+    /// A question that is answered with a secret, such as a password. The answer is not echoed
+    /// to the terminal, or each char is replaced by a mask char such as '*'. The secret can
+    /// optionally be asked for twice, in which case the question is repeated until both
+    /// answers are the same.
+    #[derive(Debug, Clone)]
+    pub struct Password {
+        prompt: String,
+        confirmation: Option<(String, String)>,
+        mask: Option<char>,
+        style: Style,
+    }
+
+    impl Password {
+        /// Creates a new question without confirmation or a mask, printed in ```Style::Bold```.
+        pub fn new(prompt: &str) -> Self {
+            Self {
+                prompt: String::from(prompt),
+                confirmation: None,
+                mask: None,
+                style: Style::Bold,
+            }
+        }
+
+        /// Returns the question.
+        pub fn prompt(&self) -> &str {
+            &self.prompt
+        }
+
+        /// Asks for the secret a second time using ```prompt``` and prints ```mismatch``` if the two
+        /// answers are not the same.
+        pub fn set_confirmation(&mut self, prompt: &str, mismatch: &str) {
+            self.confirmation = Some((String::from(prompt), String::from(mismatch)));
+        }
+
+        /// Stops asking for the secret a second time.
+        pub fn clear_confirmation(&mut self) {
+            self.confirmation = None;
+        }
+
+        /// Returns the char printed for every char of the secret, if any.
+        pub fn mask(&self) -> Option<char> {
+            self.mask
+        }
+
+        /// Sets the char printed for every char of the secret. Pass ```None``` to print nothing.
+        pub fn set_mask(&mut self, mask: Option<char>) {
+            self.mask = mask;
+        }
+
+        /// Returns the text::Style used to print the question.
+        pub fn style(&self) -> Style {
+            self.style
+        }
+
+        /// Sets the text::Style used to print the question.
+        pub fn set_style(&mut self, style: Style) {
+            self.style = style;
+        }
+
+        /// A private function that formats ```prompt``` using the text style.
+        fn render_prompt(&self, prompt: &str) -> String {
+            format!("{}{}{} ", self.style, prompt, Style::Regular)
+        }
+
+        /// Asks for the secret on stdout and reads it from stdin with echo turned off. Returns an
+        /// error of kind ```InvalidInput``` if stdin is not a terminal. The terminal is restored
+        /// even if the program panics or is interrupted with Ctrl-C. If prompts are scripted,
+        /// then the next scripted answer is returned instead and is not printed.
+        pub fn ask(&self) -> io::Result<Secret> {
+            if let Some(answer) = next_answer(&self.prompt) {
+                return self.answer_with(answer?, io::stdout());
+            }
+            if !io::stdin().is_terminal() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "cannot ask \"{}\" because stdin is not a terminal",
+                        self.prompt
+                    ),
+                ));
+            }
+            loop {
+                let secret: Secret = read_secret(&self.render_prompt(&self.prompt), self.mask)?;
+                let (prompt, mismatch) = match &self.confirmation {
+                    Some(confirmation) => confirmation,
+                    None => return Ok(secret),
+                };
+                if read_secret(&self.render_prompt(prompt), self.mask)? == secret {
+                    return Ok(secret);
+                }
+                write_error(&mut io::stdout(), mismatch)?;
+            }
+        }
+
+        /// A private function that answers with the ```scripted``` secret without waiting for the
+        /// user, and prints the prompt, but not the secret, on ```output```.
+        fn answer_with<W: Write>(&self, scripted: String, mut output: W) -> io::Result<Secret> {
+            let secret: Secret = Secret::new(scripted);
+            writeln!(output, "{}", self.render_prompt(&self.prompt))?;
+            Ok(secret)
+        }
+
+        /// Asks for the secret on ```output``` and reads it from ```input``` without changing the
+        /// terminal. Returns an error of kind ```UnexpectedEof``` at the end of the input, or of
+        /// kind ```InvalidInput``` if the secret is longer than ```MAX_SECRET_LEN``` bytes.
+        pub fn ask_with<R: BufRead, W: Write>(
+            &self,
+            mut input: R,
+            mut output: W,
+        ) -> io::Result<Secret> {
+            let mut read = |prompt: &str, output: &mut W| -> io::Result<Secret> {
+                write!(output, "{}", self.render_prompt(prompt))?;
+                output.flush()?;
+                let secret: Secret = read_secret_line(&mut input)?
+                    .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+                writeln!(output)?;
+                Ok(secret)
+            };
+            loop {
+                let secret: Secret = read(&self.prompt, &mut output)?;
+                let (prompt, mismatch) = match &self.confirmation {
+                    Some(confirmation) => confirmation,
+                    None => return Ok(secret),
+                };
+                if read(prompt, &mut output)? == secret {
+                    return Ok(secret);
+                }
+                write_error(&mut output, mismatch)?;
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+
+        use super::*;
+
+        fn score(pattern: &str, candidate: &str) -> Option<i64> {
+            fuzzy_match(pattern, candidate).map(|(score, _)| score)
+        }
+
+        #[test]
+        fn fuzzy_match_finds_subsequences() {
+            assert_eq!(
+                fuzzy_match("abc", "xaxbxc").map(|(_, p)| p),
+                Some(vec![1, 3, 5])
+            );
+            assert_eq!(fuzzy_match("abd", "abc"), None);
+            assert_eq!(fuzzy_match("", "abc"), Some((0, Vec::new())));
+            assert_eq!(fuzzy_match("a", ""), None);
+        }
+
+        #[test]
+        fn fuzzy_match_is_case_sensitive_only_with_uppercase() {
+            assert!(fuzzy_match("ab", "AB").is_some());
+            assert!(fuzzy_match("Ab", "ab").is_none());
+            assert!(fuzzy_match("Ab", "xAb").is_some());
+        }
+
+        #[test]
+        fn fuzzy_score_prefers_contiguous_matches_and_word_starts() {
+            assert!(score("abc", "abcxx") > score("abc", "axbxc"));
+            assert!(score("fb", "foo_bar") > score("fb", "afoobar"));
+            assert!(score("fb", "FooBar") > score("fb", "afoobar"));
+        }
+
+        #[test]
+        fn fuzzy_score_reuses_the_chars_buffer() {
+            let pattern: Vec<char> = "ab".chars().collect();
+            let mut chars: Vec<char> = Vec::new();
+            let mut positions: Vec<usize> = vec![9, 9, 9];
+            let first: Option<i64> =
+                fuzzy_score(&pattern, "xab", false, &mut chars, Some(&mut positions));
+            assert!(first.is_some());
+            assert_eq!(positions, [1, 2]);
+            assert_eq!(chars, ['x', 'a', 'b']);
+            assert_eq!(fuzzy_score(&pattern, "b", false, &mut chars, None), None);
+            assert_eq!(chars, ['b']);
+        }
+
+        /// Scripted answers are shared by the whole process, so the tests that use them take turns.
+        static SCRIPT_TESTS: Mutex<()> = Mutex::new(());
+
+        fn with_answers<F: FnOnce()>(answers: &[&str], f: F) {
+            let _lock = SCRIPT_TESTS.lock().unwrap_or_else(|e| e.into_inner());
+            set_answers(answers.iter().copied());
+            f();
+            clear_answers();
+        }
+
+        fn kind<T: std::fmt::Debug>(result: io::Result<T>) -> io::ErrorKind {
+            result.unwrap_err().kind()
+        }
+
+        #[test]
+        fn scripted_answers_are_read_from_the_answers_file() {
+            let path: std::path::PathBuf =
+                std::env::temp_dir().join(format!("cli_tools_answers_{}", std::process::id()));
+            fs::write(&path, "yes\n\n42\r\n").unwrap();
+            let mut script: Script = Script {
+                answers: None,
+                env_checked: false,
+            };
+            load_answers_from(&mut script, &path).unwrap();
+            fs::remove_file(&path).unwrap();
+            assert_eq!(
+                script.answers,
+                Some(VecDeque::from(["yes".into(), "".into(), "42".into()]))
+            );
+            let error: io::Error = load_answers_from(&mut script, &path).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::NotFound);
+            assert_eq!(script.answers.map(|a| a.len()), Some(3));
+        }
+
+        #[test]
+        fn running_out_of_answers_is_an_error() {
+            with_answers(&["yes"], || {
+                assert_eq!(next_answer("Continue?").unwrap().unwrap(), "yes");
+                assert_eq!(remaining_answers(), Some(0));
+                let error: io::Error = Confirm::new("Really?").ask().unwrap_err();
+                assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+                assert!(error.to_string().contains("Really?"));
+                assert_eq!(
+                    kind(Input::<String>::new("Name:").ask()),
+                    io::ErrorKind::UnexpectedEof
+                );
+            });
+        }
+
+        #[test]
+        fn invalid_scripted_answers_are_rejected() {
+            let answers: [&str; 7] = ["maybe", "abc", "7", "4", "", "a,x", "nothing"];
+            with_answers(&answers, || {
+                let invalid: io::ErrorKind = io::ErrorKind::InvalidData;
+                assert_eq!(kind(Confirm::new("Continue?").ask()), invalid);
+                assert_eq!(kind(Input::<u32>::new("Count:").ask()), invalid);
+                let mut even: Input<u32> = Input::new("Even:");
+                even.add_validator(|n| match n % 2 {
+                    0 => Ok(()),
+                    _ => Err(String::from("Please enter an even number.")),
+                });
+                assert_eq!(kind(even.ask()), invalid);
+                let mut select: Select = Select::new("Pick:");
+                for item in ["a", "b", "c"] {
+                    select.add_item(item);
+                }
+                assert_eq!(kind(select.ask()), invalid);
+                assert_eq!(kind(select.ask()), invalid);
+                let mut multi: MultiSelect = MultiSelect::new("Pick:");
+                for item in ["a", "b", "c"] {
+                    multi.add_item(item, false);
+                }
+                assert_eq!(kind(multi.ask()), invalid);
+                let mut fuzzy: FuzzySelect = FuzzySelect::new("Find:");
+                fuzzy.add_item("apple");
+                assert_eq!(kind(fuzzy.ask()), invalid);
+            });
+        }
+
+        /// Answers ```prompt``` with ```answer```, or its default if ```answer``` is ```None```,
+        /// and returns the value with what was printed, without styles.
+        fn answer<T, F>(answer: Option<&str>, f: F) -> (T, String)
+        where
+            F: FnOnce(Option<String>, &mut Vec<u8>) -> io::Result<T>,
+        {
+            let mut output: Vec<u8> = Vec::new();
+            let value: T = f(answer.map(String::from), &mut output).unwrap();
+            (
+                value,
+                text::strip_escapes(&String::from_utf8(output).unwrap()),
+            )
+        }
+
+        #[test]
+        fn scripted_answers_are_printed_after_the_question() {
+            let mut confirm: Confirm = Confirm::new("Continue?");
+            confirm.set_default(Some(false));
+            let hint: &str = confirm.hint();
+            let printed = |s: &str| format!("Continue? {} {}\n", hint, s);
+            assert_eq!(
+                answer(Some(""), |a, o| confirm.answer_with(a, o)),
+                (false, printed("n"))
+            );
+            assert_eq!(
+                answer(Some("y"), |a, o| confirm.answer_with(a, o)),
+                (true, printed("y"))
+            );
+            assert_eq!(
+                answer(None, |a, o| confirm.answer_with(a, o)),
+                (false, printed("n"))
+            );
+            let count: Input<u32> = Input::new("Count:");
+            assert_eq!(
+                answer(Some("42"), |a, o| count.answer_with(a, o)),
+                (42, String::from("Count: 42\n"))
+            );
+            let mut name: Input<String> = Input::new("Name:");
+            name.set_initial_text("anon");
+            assert_eq!(
+                answer(Some(""), |a, o| name.answer_with(a, o)),
+                (String::from("anon"), String::from("Name: anon\n"))
+            );
+            let password: Password = Password::new("Password:");
+            let mut output: Vec<u8> = Vec::new();
+            let secret: Secret = password
+                .answer_with(String::from("hunter2"), &mut output)
+                .unwrap();
+            assert_eq!(secret.expose(), "hunter2");
+            let output: String = String::from_utf8(output).unwrap();
+            assert_eq!(text::strip_escapes(&output), "Password: \n");
+        }
+
+        #[test]
+        fn scripted_answers_choose_menu_items() {
+            let mut select: Select = Select::new("Pick:");
+            for item in ["a", "b", "c"] {
+                select.add_item(item);
+            }
+            select.set_default(Some(0));
+            let chosen = |a: Option<&str>| answer(a, |a, o| select.answer_with(a, o));
+            assert_eq!(chosen(Some("b")), (1, String::from("Pick: b\n")));
+            assert_eq!(chosen(Some("3")), (2, String::from("Pick: c\n")));
+            assert_eq!(chosen(Some("")), (0, String::from("Pick: a\n")));
+            assert_eq!(chosen(None), (0, String::from("Pick: a\n")));
+            let mut multi: MultiSelect = MultiSelect::new("Pick:");
+            for item in ["a", "b", "c"] {
+                multi.add_item(item, item == "b");
+            }
+            let chosen = |a: Option<&str>| answer(a, |a, o| multi.answer_with(a, o));
+            assert_eq!(
+                chosen(Some("a, 3")),
+                (vec![0, 2], String::from("Pick: a, c\n"))
+            );
+            assert_eq!(chosen(Some("")), (vec![1], String::from("Pick: b\n")));
+            let mut fuzzy: FuzzySelect = FuzzySelect::new("Find:");
+            fuzzy.add_item("banana");
+            fuzzy.add_item("apple");
+            let chosen = |a: Option<&str>| answer(a, |a, o| fuzzy.answer_with(a, o));
+            assert_eq!(chosen(Some("pl")), (1, String::from("Find: apple\n")));
+            assert_eq!(chosen(Some("banana")), (0, String::from("Find: banana\n")));
+        }
+
+        #[test]
+        fn password_asks_again_until_the_confirmation_matches() {
+            let mut password: Password = Password::new("Password:");
+            password.set_confirmation("Again:", "no match");
+            let mut output: Vec<u8> = Vec::new();
+            let secret: Secret = password
+                .ask_with(&b"a\nb\nc\r\nc\n"[..], &mut output)
+                .unwrap();
+            assert_eq!(secret.expose(), "c");
+            let output: String = String::from_utf8(output).unwrap();
+            assert_eq!(output.matches("no match").count(), 1);
+            assert_eq!(output.matches("Again:").count(), 2);
+            let error: io::Error = password.ask_with(&b"a\n"[..], Vec::new()).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+        }
+
+        #[test]
+        fn secrets_longer_than_the_limit_are_rejected() {
+            let mut input: Vec<u8> = vec![b'x'; MAX_SECRET_LEN + 1];
+            input.push(b'\n');
+            let error: io::Error = Password::new("Password:")
+                .ask_with(&input[..], Vec::new())
+                .unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        }
+
+        /// A menu of ```len``` items that shows ```height``` of them, whatever the terminal size.
+        fn menu(len: usize, height: usize) -> Menu {
+            Menu {
+                len,
+                cursor: 0,
+                offset: 0,
+                height,
+                cols: 80,
+                up: 0,
+            }
+        }
+
+        fn key(code: KeyCode) -> KeyEvent {
+            KeyEvent::Key(code, Modifiers::NONE)
+        }
+
+        #[test]
+        fn menu_arrows_wrap_around_at_both_ends() {
+            let mut menu: Menu = menu(5, 10);
+            assert!(menu.navigate(&key(KeyCode::Up), false));
+            assert_eq!(menu.cursor, 4);
+            assert!(menu.navigate(&key(KeyCode::Down), false));
+            assert_eq!(menu.cursor, 0);
+            assert!(menu.navigate(&key(KeyCode::Down), false));
+            assert_eq!(menu.cursor, 1);
+            assert!(menu.navigate(&key(KeyCode::End), false));
+            assert_eq!(menu.cursor, 4);
+            assert!(menu.navigate(&key(KeyCode::Home), false));
+            assert_eq!(menu.cursor, 0);
+        }
+
+        #[test]
+        fn menu_pages_stop_at_the_first_and_last_item() {
+            let mut menu: Menu = menu(10, 4);
+            assert!(menu.navigate(&key(KeyCode::PageDown), false));
+            assert_eq!(menu.cursor, 4);
+            assert!(menu.navigate(&key(KeyCode::PageDown), false));
+            assert_eq!(menu.cursor, 8);
+            assert!(menu.navigate(&key(KeyCode::PageDown), false));
+            assert_eq!(menu.cursor, 9);
+            assert!(menu.navigate(&key(KeyCode::PageUp), false));
+            assert_eq!(menu.cursor, 5);
+            assert!(menu.navigate(&key(KeyCode::PageUp), false));
+            assert!(menu.navigate(&key(KeyCode::PageUp), false));
+            assert_eq!(menu.cursor, 0);
+        }
+
+        #[test]
+        fn menu_vi_keys_only_move_when_enabled() {
+            let mut menu: Menu = menu(3, 10);
+            assert!(!menu.navigate(&key(KeyCode::Char('j')), false));
+            assert!(!menu.navigate(&key(KeyCode::Char('k')), false));
+            assert_eq!(menu.cursor, 0);
+            assert!(menu.navigate(&key(KeyCode::Char('k')), true));
+            assert_eq!(menu.cursor, 2);
+            assert!(menu.navigate(&key(KeyCode::Char('j')), true));
+            assert_eq!(menu.cursor, 0);
+            assert!(menu.navigate(&key(KeyCode::Char('j')), true));
+            assert_eq!(menu.cursor, 1);
+            let ctrl_j: KeyEvent = KeyEvent::Key(KeyCode::Char('j'), Modifiers::CTRL);
+            assert!(!menu.navigate(&ctrl_j, true));
+            assert!(!menu.navigate(&key(KeyCode::Char('x')), true));
+            assert_eq!(menu.cursor, 1);
+        }
+
+        #[test]
+        fn menu_scrolls_to_keep_the_cursor_visible() {
+            let mut menu: Menu = menu(10, 3);
+            let visible = |menu: &Menu| menu.offset <= menu.cursor && menu.cursor < menu.offset + 3;
+            for expected in [(1, 0), (2, 0), (3, 1), (4, 2)] {
+                menu.navigate(&key(KeyCode::Down), false);
+                assert_eq!((menu.cursor, menu.offset), expected);
+            }
+            for expected in [(3, 2), (2, 2), (1, 1), (0, 0)] {
+                menu.navigate(&key(KeyCode::Up), false);
+                assert_eq!((menu.cursor, menu.offset), expected);
+            }
+            menu.navigate(&key(KeyCode::Up), false);
+            assert_eq!((menu.cursor, menu.offset), (9, 7));
+            menu.navigate(&key(KeyCode::Down), false);
+            assert_eq!((menu.cursor, menu.offset), (0, 0));
+            for code in [
+                KeyCode::PageDown,
+                KeyCode::End,
+                KeyCode::PageUp,
+                KeyCode::Home,
+            ] {
+                menu.navigate(&key(code), false);
+                assert!(visible(&menu));
+            }
+            menu.move_to(20);
+            assert_eq!((menu.cursor, menu.offset), (9, 7));
+        }
+
+        #[test]
+        fn reading_resumes_on_the_line_after_a_secret_that_is_too_long() {
+            let mut input: Vec<u8> = vec![b'x'; MAX_SECRET_LEN * 2];
+            input.extend_from_slice(b"\nshort\n");
+            let mut reader: &[u8] = &input;
+            let error: io::Error = read_secret_line(&mut reader).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+            let secret: Secret = read_secret_line(&mut reader).unwrap().unwrap();
+            assert_eq!(secret.expose(), "short");
+            assert!(read_secret_line(&mut reader).unwrap().is_none());
+        }
+    }
+}
+
+/// A module for reading lines of text from the user with an editor similar to GNU readline.
+/// ```Editor::read_line()``` supports moving the cursor by char and by word, killing and yanking
+/// text, browsing and searching the history, and completing words with Tab. The history can be
+/// kept in a file so that it persists between runs of a program.
+pub mod readline {
+
+    use crate::prompt;
+    use crate::term::input::{KeyCode, KeyEvent, KeyReader, Modifiers, RawMode};
+    use crate::term::{self, Cursor, Erase};
+    use crate::text;
+    use std::fmt;
+    use std::fs;
+    use std::io::{self, BufRead, IsTerminal, Write};
+    use std::path::{Path, PathBuf};
+
+    /// A trait for types that suggest completions for the word in front of the cursor when the
+    /// user presses Tab.
+    pub trait Completer {
+        /// Returns the byte index in ```line``` where the word being completed starts, and the
+        /// candidates that can replace the text between that index and ```pos```, which is the
+        /// byte index of the cursor. An index past ```pos``` is treated as ```pos```, and an index
+        /// inside a char is moved back to the start of that char.
+        fn complete(&self, line: &str, pos: usize) -> (usize, Vec<String>);
+    }
+
+    impl<F> Completer for F
+    where
+        F: Fn(&str, usize) -> (usize, Vec<String>),
+    {
+        fn complete(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+            self(line, pos)
+        }
+    }
+
+    /// A completer that completes the word in front of the cursor from a fixed list of words,
+    /// e.g. the commands of a REPL. Words are separated by whitespace.
+    #[derive(Debug, Clone, Default)]
+    pub struct WordCompleter {
+        words: Vec<String>,
+    }
+
+    impl WordCompleter {
+        /// Creates a new completer for ```words```.
+        pub fn new<S: AsRef<str>>(words: &[S]) -> Self {
+            Self {
+                words: words.iter().map(|w| String::from(w.as_ref())).collect(),
+            }
+        }
+
+        /// Returns the words.
+        pub fn words(&self) -> &[String] {
+            &self.words
+        }
+    }
+
+    impl Completer for WordCompleter {
+        fn complete(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+            let start: usize = line[..pos].rfind(char::is_whitespace).map_or(0, |i| {
+                i + line[i..].chars().next().map_or(1, char::len_utf8)
+            });
+            let prefix: &str = &line[start..pos];
+            let candidates: Vec<String> = self
+                .words
+                .iter()
+                .filter(|w| w.starts_with(prefix))
+                .cloned()
+                .collect();
+            (start, candidates)
+        }
+    }
+
+    /// A list of previously entered lines, oldest first. Empty lines and lines that repeat the
+    /// newest entry are not added. When the list is full, the oldest entry is removed.
+    #[derive(Debug, Clone)]
+    pub struct History {
+        entries: Vec<String>,
+        max_len: usize,
+    }
+
+    impl Default for History {
+        /// Creates an empty history of at most 1000 entries.
+        fn default() -> Self {
+            Self::new(1000)
+        }
+    }
+
+    impl History {
+        /// Creates an empty history of at most ```max_len``` entries.
+        pub fn new(max_len: usize) -> Self {
+            Self {
+                entries: Vec::new(),
+                max_len,
+            }
+        }
+
+        /// Returns the maximum number of entries.
+        pub fn max_len(&self) -> usize {
+            self.max_len
+        }
+
+        /// Sets the maximum number of entries, removing the oldest entries if there are too many.
+        pub fn set_max_len(&mut self, max_len: usize) {
+            self.max_len = max_len;
+            self.trim();
+        }
+
+        /// Returns the entries, oldest first.
+        pub fn entries(&self) -> &[String] {
+            &self.entries
+        }
+
+        /// Returns the number of entries.
+        pub fn len(&self) -> usize {
+            self.entries.len()
+        }
+
+        /// Returns ```true``` if there are no entries.
+        pub fn is_empty(&self) -> bool {
+            self.entries.is_empty()
+        }
+
+        /// Adds ```line``` as the newest entry. Returns ```false``` if the line was not added
+        /// because it is blank or the same as the newest entry.
+        pub fn add(&mut self, line: &str) -> bool {
+            if line.trim().is_empty() || self.entries.last().is_some_and(|last| last == line) {
+                return false;
+            }
+            self.entries.push(String::from(line));
+            self.trim();
+            true
+        }
+
+        /// Removes all entries.
+        pub fn clear(&mut self) {
+            self.entries.clear();
+        }
+
+        /// Adds the lines of the file at ```path``` as entries. A file that does not exist is
+        /// treated as empty.
+        pub fn load(&mut self, path: &Path) -> io::Result<()> {
+            let file: fs::File = match fs::File::open(path) {
+                Ok(file) => file,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+                Err(e) => return Err(e),
+            };
+            for line in io::BufReader::new(file).lines() {
+                self.add(&line?);
+            }
+            Ok(())
+        }
+
+        /// Writes the entries to the file at ```path```, one per line, replacing its contents.
+        pub fn save(&self, path: &Path) -> io::Result<()> {
+            let mut file = io::BufWriter::new(fs::File::create(path)?);
+            for entry in &self.entries {
+                writeln!(file, "{}", entry)?;
+            }
+            file.flush()
+        }
+
+        /// A private function that removes the oldest entries until there are at most
+        /// ```max_len```.
+        fn trim(&mut self) {
+            if self.entries.len() > self.max_len {
+                self.entries.drain(..self.entries.len() - self.max_len);
+            }
+        }
+
+        /// A private function that returns the index of the newest entry before ```before```
+        /// that contains ```query```.
+        fn search(&self, query: &str, before: usize) -> Option<usize> {
+            self.entries[..before.min(self.entries.len())]
+                .iter()
+                .rposition(|entry| entry.contains(query))
+        }
+    }
+
+    /// A private function that returns the number of columns taken up by ```chars```.
+    fn width(chars: &[char]) -> usize {
+        chars.iter().map(|&c| text::char_width(c)).sum()
+    }
+
+    /// A private struct that holds the line being edited, the position of the cursor in chars,
+    /// and the first char shown when the line is too long to fit in the terminal.
+    #[derive(Default)]
+    struct Buffer {
+        chars: Vec<char>,
+        cursor: usize,
+        scroll: usize,
+    }
+
+    impl Buffer {
+        /// Replaces the line with ```line``` and moves the cursor to its end.
+        fn set(&mut self, line: &str) {
+            self.chars = line.chars().collect();
+            self.cursor = self.chars.len();
+        }
+
+        /// Returns the line as a string.
+        fn line(&self) -> String {
+            self.chars.iter().collect()
+        }
+
+        /// Inserts the printable chars of ```s``` in front of the cursor.
+        fn insert(&mut self, s: &str) {
+            for c in s.chars().filter(|c| !c.is_control()) {
+                self.chars.insert(self.cursor, c);
+                self.cursor += 1;
+            }
+        }
+
+        /// Removes the chars between ```start``` and ```end```, moves the cursor to ```start```,
+        /// and returns the removed chars.
+        fn remove(&mut self, start: usize, end: usize) -> String {
+            self.cursor = start;
+            self.chars.drain(start..end).collect()
+        }
+
+        /// Returns the index of the start of the word in front of the cursor. Words are runs of
+        /// alphanumeric chars, or of anything but whitespace if ```big``` is ```true```.
+        fn word_start(&self, big: bool) -> usize {
+            let in_word = |c: char| {
+                if big {
+                    !c.is_whitespace()
+                } else {
+                    c.is_alphanumeric()
+                }
+            };
+            let mut i: usize = self.cursor;
+            while i > 0 && !in_word(self.chars[i - 1]) {
+                i -= 1;
+            }
+            while i > 0 && in_word(self.chars[i - 1]) {
+                i -= 1;
+            }
+            i
+        }
+
+        /// Returns the index of the end of the word behind the cursor.
+        fn word_end(&self) -> usize {
+            let mut i: usize = self.cursor;
+            while i < self.chars.len() && !self.chars[i].is_alphanumeric() {
+                i += 1;
+            }
+            while i < self.chars.len() && self.chars[i].is_alphanumeric() {
+                i += 1;
+            }
+            i
+        }
+
+        /// Redraws the line after ```prompt```. If the line does not fit in ```cols``` columns,
+        /// then only the part around the cursor is shown.
+        fn render<W: Write>(
+            &mut self,
+            output: &mut W,
+            prompt: &str,
+            cols: usize,
+        ) -> io::Result<()> {
+            render_line(
+                output,
+                prompt,
+                &self.chars,
+                self.cursor,
+                &mut self.scroll,
+                cols,
+            )
+        }
+    }
+
+    /// A private function that redraws ```chars``` after ```prompt``` with the cursor in front of
+    /// the char at ```cursor```. If the line does not fit in ```cols``` columns, then only the
+    /// part around the cursor is shown, starting at the char at ```scroll```. ```scroll``` is
+    /// moved just far enough to keep the cursor visible, so the line does not jump around while
+    /// the cursor moves. It is shared with ```prompt::Input```, which edits a single line too.
+    pub(crate) fn render_line<W: Write>(
+        output: &mut W,
+        prompt: &str,
+        chars: &[char],
+        cursor: usize,
+        scroll: &mut usize,
+        cols: usize,
+    ) -> io::Result<()> {
+        let prompt_width: usize = text::visible_width(prompt);
+        let available: usize = cols.saturating_sub(prompt_width + 1).max(1);
+        *scroll = usize::min(*scroll, cursor);
+        while width(&chars[*scroll..cursor]) > available {
+            *scroll += 1;
+        }
+        let mut shown: String = String::new();
+        let mut used: usize = 0;
+        for &c in &chars[*scroll..] {
+            used += text::char_width(c);
+            if used > available {
+                break;
+            }
+            shown.push(c);
+        }
+        let column: usize = prompt_width + width(&chars[*scroll..cursor]);
+        write!(
+            output,
+            "\r{}{}{}{}",
+            Erase::Line,
+            prompt,
+            shown,
+            Cursor::ToColumn(column.min(u16::MAX as usize) as u16)
+        )?;
+        output.flush()
+    }
+
+    /// A private struct that holds the state of a reverse history search started with Ctrl-R.
+    struct Search {
+        query: String,
+        found: Option<usize>,
+    }
+
+    /// A line editor that keeps a history of the lines it has read.
+    ///
+    /// | Keys                         | Action                                           |
+    /// |------------------------------|--------------------------------------------------|
+    /// | Left, Right, Ctrl-B, Ctrl-F  | Move the cursor by one char                      |
+    /// | Alt-B, Alt-F, Ctrl-Left/Right| Move the cursor by one word                      |
+    /// | Home, End, Ctrl-A, Ctrl-E    | Move the cursor to the start or end of the line  |
+    /// | Backspace, Delete            | Delete the char in front of or behind the cursor |
+    /// | Ctrl-K, Ctrl-U               | Kill the text behind or in front of the cursor   |
+    /// | Ctrl-W, Alt-Backspace, Alt-D | Kill the word in front of or behind the cursor   |
+    /// | Ctrl-Y                       | Yank the most recently killed text               |
+    /// | Up, Down, Ctrl-P, Ctrl-N     | Browse the history                               |
+    /// | Ctrl-R                       | Search the history backwards                     |
+    /// | Tab                          | Complete the word in front of the cursor         |
+    /// | Ctrl-L                       | Clear the screen                                 |
+    /// | Ctrl-D                       | End the input if the line is empty               |
+    /// | Ctrl-C                       | Cancel the line                                  |
+    pub struct Editor {
+        history: History,
+        history_file: Option<PathBuf>,
+        completer: Option<Box<dyn Completer>>,
+        kill_buffer: String,
+    }
+
+    impl fmt::Debug for Editor {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Editor")
+                .field("history", &self.history)
+                .field("history_file", &self.history_file)
+                .field("completer", &self.completer.is_some())
+                .finish()
+        }
+    }
+
+    impl Default for Editor {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl Editor {
+        /// Creates a new editor with an empty history of at most 1000 entries and no completer.
+        pub fn new() -> Self {
+            Self {
+                history: History::default(),
+                history_file: None,
+                completer: None,
+                kill_buffer: String::new(),
+            }
+        }
+
+        /// Returns the history.
+        pub fn history(&self) -> &History {
+            &self.history
+        }
+
+        /// Returns the history so that entries can be added or removed.
+        pub fn history_mut(&mut self) -> &mut History {
+            &mut self.history
+        }
+
+        /// Returns the file the history is kept in, if any.
+        pub fn history_file(&self) -> Option<&Path> {
+            self.history_file.as_deref()
+        }
+
+        /// Loads the history from the file at ```path``` and saves it there every time a line is
+        /// read. A file that does not exist is created when the first line is read. Errors
+        /// while saving are ignored; call ```History::save()``` to handle them.
+        pub fn set_history_file(&mut self, path: &Path) -> io::Result<()> {
+            self.history.load(path)?;
+            self.history_file = Some(path.to_path_buf());
+            Ok(())
+        }
+
+        /// Sets the completer used when the user presses Tab.
+        pub fn set_completer<C: Completer + 'static>(&mut self, completer: C) {
+            self.completer = Some(Box::new(completer));
+        }
+
+        /// Removes the completer.
+        pub fn clear_completer(&mut self) {
+            self.completer = None;
+        }
+
+        /// Prints ```prompt```, which may contain styles, and lets the user edit a line in raw
+        /// mode. The line is added to the history. Returns ```None``` if Ctrl-D is pressed on an
+        /// empty line and an error of kind ```Interrupted``` if Ctrl-C is pressed. If stdin is
+        /// not a terminal, then a line is read from stdin without editing and ```None``` is
+        /// returned at the end of the input. If prompts are scripted, then the next scripted
+        /// answer is printed and returned instead. See ```prompt::set_answers()```.
+        pub fn read_line(&mut self, prompt: &str) -> io::Result<Option<String>> {
+            let mut stdout = io::stdout();
+            let line: Option<String> = if let Some(answer) = prompt::next_answer(prompt) {
+                let answer: String = answer?;
+                writeln!(stdout, "{}{}", prompt, answer)?;
+                Some(answer)
+            } else if io::stdin().is_terminal() {
+                self.edit(prompt)?
+            } else {
+                write!(stdout, "{}", prompt)?;
+                stdout.flush()?;
+                let mut line: String = String::new();
+                if io::stdin().lock().read_line(&mut line)? == 0 {
+                    return Ok(None);
+                }
+                let len: usize = line.trim_end_matches(['\r', '\n']).len();
+                line.truncate(len);
+                Some(line)
+            };
+            if let Some(line) = &line {
+                if self.history.add(line) {
+                    if let Some(path) = &self.history_file {
+                        let _ = self.history.save(path);
+                    }
+                }
+            }
+            Ok(line)
+        }
+
+        /// A private function that runs the editor in raw mode.
+        fn edit(&mut self, prompt: &str) -> io::Result<Option<String>> {
+            let _raw: RawMode = RawMode::enable()?;
+            let mut keys = KeyReader::stdin();
+            let mut stdout = io::stdout();
+            let mut buffer: Buffer = Buffer::default();
+            let mut search: Option<Search> = None;
+            let mut hist_index: usize = self.history.len();
+            let mut saved: String = String::new();
+            let mut killing: bool = false;
+            loop {
+                let cols: usize = term::size().map_or(80, |size| size.cols as usize);
+                match &search {
+                    Some(s) => self.render_search(&mut stdout, s)?,
+                    None => buffer.render(&mut stdout, prompt, cols)?,
+                }
+                let key: KeyEvent = keys.read_key()?;
+                if let Some(s) = &mut search {
+                    let len: usize = self.history.len();
+                    match key {
+                        KeyEvent::Key(KeyCode::Char('r'), Modifiers::CTRL) => {
+                            if let Some(found) =
+                                self.history.search(&s.query, s.found.unwrap_or(len))
+                            {
+                                s.found = Some(found);
+                            }
+                            continue;
+                        }
+                        KeyEvent::Key(KeyCode::Char('g'), Modifiers::CTRL)
+                        | KeyEvent::Key(KeyCode::Esc, _) => {
+                            search = None;
+                            continue;
+                        }
+                        KeyEvent::Key(KeyCode::Backspace, _) => {
+                            s.query.pop();
+                            s.found = self.history.search(&s.query, len);
+                            continue;
+                        }
+                        KeyEvent::Key(KeyCode::Char(c), mods)
+                            if !mods.contains(Modifiers::CTRL) =>
+                        {
+                            s.query.push(c);
+                            s.found = self
+                                .history
+                                .search(&s.query, s.found.map_or(len, |f| f + 1));
+                            continue;
+                        }
+                        _ => {
+                            // any other key accepts the match and is then handled as usual
+                            if let Some(found) = s.found {
+                                buffer.set(&self.history.entries[found]);
+                                hist_index = found;
+                            }
+                            search = None;
+                        }
+                    }
+                }
+                let was_killing: bool = std::mem::take(&mut killing);
+                match key {
+                    KeyEvent::Key(KeyCode::Enter, _) => {
+                        buffer.render(&mut stdout, prompt, cols)?;
+                        writeln!(stdout)?;
+                        return Ok(Some(buffer.line()));
+                    }
+                    KeyEvent::Key(KeyCode::Char('c'), Modifiers::CTRL) => {
+                        writeln!(stdout)?;
+                        return Err(io::Error::from(io::ErrorKind::Interrupted));
+                    }
+                    KeyEvent::Key(KeyCode::Char('d'), Modifiers::CTRL)
+                        if buffer.chars.is_empty() =>
+                    {
+                        writeln!(stdout)?;
+                        return Ok(None);
+                    }
+                    KeyEvent::Key(KeyCode::Left, Modifiers::CTRL)
+                    | KeyEvent::Key(KeyCode::Char('b'), Modifiers::ALT) => {
+                        buffer.cursor = buffer.word_start(false);
+                    }
+                    KeyEvent::Key(KeyCode::Right, Modifiers::CTRL)
+                    | KeyEvent::Key(KeyCode::Char('f'), Modifiers::ALT) => {
+                        buffer.cursor = buffer.word_end();
+                    }
+                    KeyEvent::Key(KeyCode::Left, _)
+                    | KeyEvent::Key(KeyCode::Char('b'), Modifiers::CTRL) => {
+                        buffer.cursor = buffer.cursor.saturating_sub(1);
+                    }
+                    KeyEvent::Key(KeyCode::Right, _)
+                    | KeyEvent::Key(KeyCode::Char('f'), Modifiers::CTRL) => {
+                        buffer.cursor = usize::min(buffer.cursor + 1, buffer.chars.len());
+                    }
+                    KeyEvent::Key(KeyCode::Home, _)
+                    | KeyEvent::Key(KeyCode::Char('a'), Modifiers::CTRL) => {
+                        buffer.cursor = 0;
+                    }
+                    KeyEvent::Key(KeyCode::End, _)
+                    | KeyEvent::Key(KeyCode::Char('e'), Modifiers::CTRL) => {
+                        buffer.cursor = buffer.chars.len();
+                    }
+                    KeyEvent::Key(KeyCode::Backspace, Modifiers::ALT) => {
+                        let start: usize = buffer.word_start(false);
+                        let killed: String = buffer.remove(start, buffer.cursor);
+                        self.kill(killed, true, was_killing);
+                        killing = true;
+                    }
+                    KeyEvent::Key(KeyCode::Backspace, _) if buffer.cursor > 0 => {
+                        buffer.remove(buffer.cursor - 1, buffer.cursor);
+                    }
+                    KeyEvent::Key(KeyCode::Delete, _)
+                    | KeyEvent::Key(KeyCode::Char('d'), Modifiers::CTRL)
+                        if buffer.cursor < buffer.chars.len() =>
+                    {
+                        buffer.remove(buffer.cursor, buffer.cursor + 1);
+                    }
+                    KeyEvent::Key(KeyCode::Char('k'), Modifiers::CTRL) => {
+                        let killed: String = buffer.remove(buffer.cursor, buffer.chars.len());
+                        self.kill(killed, false, was_killing);
+                        killing = true;
+                    }
+                    KeyEvent::Key(KeyCode::Char('u'), Modifiers::CTRL) => {
+                        let killed: String = buffer.remove(0, buffer.cursor);
+                        self.kill(killed, true, was_killing);
+                        killing = true;
+                    }
+                    KeyEvent::Key(KeyCode::Char('w'), Modifiers::CTRL) => {
+                        let start: usize = buffer.word_start(true);
+                        let killed: String = buffer.remove(start, buffer.cursor);
+                        self.kill(killed, true, was_killing);
+                        killing = true;
+                    }
+                    KeyEvent::Key(KeyCode::Char('d'), Modifiers::ALT) => {
+                        let (start, end) = (buffer.cursor, buffer.word_end());
+                        let killed: String = buffer.remove(start, end);
+                        self.kill(killed, false, was_killing);
+                        killing = true;
+                    }
+                    KeyEvent::Key(KeyCode::Char('y'), Modifiers::CTRL) => {
+                        buffer.insert(&self.kill_buffer)
+                    }
+                    KeyEvent::Key(KeyCode::Up, _)
+                    | KeyEvent::Key(KeyCode::Char('p'), Modifiers::CTRL)
+                        if hist_index > 0 =>
+                    {
+                        if hist_index == self.history.len() {
+                            saved = buffer.line();
+                        }
+                        hist_index -= 1;
+                        buffer.set(&self.history.entries[hist_index]);
+                    }
+                    KeyEvent::Key(KeyCode::Down, _)
+                    | KeyEvent::Key(KeyCode::Char('n'), Modifiers::CTRL)
+                        if hist_index < self.history.len() =>
+                    {
+                        hist_index += 1;
+                        match self.history.entries.get(hist_index) {
+                            Some(entry) => buffer.set(entry),
+                            None => buffer.set(&saved),
+                        }
+                    }
+                    KeyEvent::Key(KeyCode::Char('r'), Modifiers::CTRL) => {
+                        search = Some(Search {
+                            query: String::new(),
+                            found: None,
+                        });
+                    }
+                    KeyEvent::Key(KeyCode::Tab, Modifiers::NONE) => {
+                        self.complete(&mut stdout, &mut buffer, cols)?
+                    }
+                    KeyEvent::Key(KeyCode::Char('l'), Modifiers::CTRL) => {
+                        write!(stdout, "{}{}", Erase::Screen, Cursor::To(0, 0))?;
+                    }
+                    KeyEvent::Key(KeyCode::Char(c), mods)
+                        if !mods.contains(Modifiers::CTRL) && !mods.contains(Modifiers::ALT) =>
+                    {
+                        buffer.insert(c.encode_utf8(&mut [0; 4]));
+                    }
+                    KeyEvent::Paste(text) => buffer.insert(&text),
+                    _ => {}
+                }
+            }
+        }
+
+        /// A private function that saves killed text so it can be yanked. Text killed by
+        /// consecutive kills is joined, in front of the saved text if ```backward``` is ```true```.
+        fn kill(&mut self, killed: String, backward: bool, append: bool) {
+            if !append {
+                self.kill_buffer = killed;
+            } else if backward {
+                self.kill_buffer.insert_str(0, &killed);
+            } else {
+                self.kill_buffer.push_str(&killed);
+            }
+        }
+
+        /// A private function that shows the state of a reverse history search in place of the
+        /// prompt and the line.
+        fn render_search<W: Write>(&self, output: &mut W, search: &Search) -> io::Result<()> {
+            let prefix: String = match search.found {
+                Some(_) => format!("(reverse-i-search)'{}': ", search.query),
+                None if search.query.is_empty() => String::from("(reverse-i-search)'': "),
+                None => format!("(failed reverse-i-search)'{}': ", search.query),
+            };
+            let entry: &str = search
+                .found
+                .map_or("", |found| &self.history.entries[found]);
+            let before: usize = entry
+                .find(&search.query)
+                .map_or(0, |i| text::visible_width(&entry[..i]));
+            let column: usize = text::visible_width(&prefix) + before;
+            write!(
+                output,
+                "\r{}{}{}{}",
+                Erase::Line,
+                prefix,
+                entry,
+                Cursor::ToColumn(column.min(u16::MAX as usize) as u16)
+            )?;
+            output.flush()
+        }
+
+        /// A private function that completes the word in front of the cursor. A single
+        /// candidate replaces the word. If there are several, then the word is extended to their
+        /// longest common prefix, or if that is not possible, the candidates are listed below
+        /// the line.
+        fn complete<W: Write>(
+            &self,
+            output: &mut W,
+            buffer: &mut Buffer,
+            cols: usize,
+        ) -> io::Result<()> {
+            let completer: &dyn Completer = match &self.completer {
+                Some(completer) => completer.as_ref(),
+                None => return Ok(()),
+            };
+            let line: String = buffer.line();
+            let pos: usize = buffer.chars[..buffer.cursor]
+                .iter()
+                .map(|c| c.len_utf8())
+                .sum();
+            let (start, candidates) = completer.complete(&line, pos);
+            // the completer may return any offset, so keep it in front of the cursor and move it
+            // back to the start of the char it points into
+            let mut start: usize = start.min(pos);
+            while !line.is_char_boundary(start) {
+                start -= 1;
+            }
+            let start: usize = line[..start].chars().count();
+            let common: String = match candidates.split_first() {
+                None => {
+                    write!(output, "\x07")?;
+                    return Ok(());
+                }
+                Some((first, rest)) => {
+                    let mut common: &str = first;
+                    for candidate in rest {
+                        let len: usize = common
+                            .char_indices()
+                            .zip(candidate.chars())
+                            .find(|((_, a), b)| a != b)
+                            .map_or(common.len().min(candidate.len()), |((i, _), _)| i);
+                        common = &common[..len];
+                    }
+                    String::from(common)
+                }
+            };
+            let word: String = buffer.chars[start..buffer.cursor].iter().collect();
+            if candidates.len() == 1 || common.chars().count() > word.chars().count() {
+                buffer.remove(start, buffer.cursor);
+                buffer.insert(&common);
+                return Ok(());
+            }
+            let column_width: usize = candidates
+                .iter()
+                .map(|c| text::visible_width(c))
+                .max()
+                .unwrap_or(0)
+                + 2;
+            let per_row: usize = (cols / column_width).max(1);
+            writeln!(output)?;
+            for row in candidates.chunks(per_row) {
+                for candidate in row {
+                    let padding: usize = column_width - text::visible_width(candidate);
+                    write!(output, "{}{:padding$}", candidate, "")?;
+                }
+                writeln!(output)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+
+        use super::*;
+
+        fn buffer(line: &str, cursor: usize) -> Buffer {
+            let mut buffer: Buffer = Buffer::default();
+            buffer.set(line);
+            buffer.cursor = cursor;
+            buffer
+        }
+
+        fn complete<C: Completer + 'static>(completer: C, line: &str) -> (String, String) {
+            let mut editor: Editor = Editor::new();
+            editor.set_completer(completer);
+            let mut buffer: Buffer = buffer(line, line.chars().count());
+            let mut output: Vec<u8> = Vec::new();
+            editor.complete(&mut output, &mut buffer, 80).unwrap();
+            (buffer.line(), String::from_utf8(output).unwrap())
+        }
+
+        #[test]
+        fn word_start_skips_separators_then_the_word() {
+            assert_eq!(buffer("foo bar", 7).word_start(false), 4);
+            assert_eq!(buffer("foo bar  ", 9).word_start(false), 4);
+            assert_eq!(buffer("foo-bar", 7).word_start(false), 4);
+            assert_eq!(buffer("foo-bar", 7).word_start(true), 0);
+            assert_eq!(buffer("a/b c", 3).word_start(true), 0);
+            assert_eq!(buffer("", 0).word_start(false), 0);
+        }
+
+        #[test]
+        fn word_end_skips_separators_then_the_word() {
+            assert_eq!(buffer("foo bar", 0).word_end(), 3);
+            assert_eq!(buffer("foo bar", 3).word_end(), 7);
+            assert_eq!(buffer("foo--", 3).word_end(), 5);
+            assert_eq!(buffer("héllo wörld", 5).word_end(), 11);
+        }
+
+        #[test]
+        fn history_skips_blank_and_repeated_lines_and_trims() {
+            let mut history: History = History::new(3);
+            assert!(history.add("a"));
+            assert!(!history.add("a"));
+            assert!(!history.add("  "));
+            assert!(history.add("b"));
+            assert!(history.add("a"));
+            assert!(history.add("c"));
+            assert_eq!(history.entries(), ["b", "a", "c"]);
+            history.set_max_len(1);
+            assert_eq!(history.entries(), ["c"]);
+        }
+
+        #[test]
+        fn history_search_finds_the_newest_match_before_an_index() {
+            let mut history: History = History::new(10);
+            for line in ["cargo build", "ls", "cargo test", "cd"] {
+                history.add(line);
+            }
+            assert_eq!(history.search("cargo", 4), Some(2));
+            assert_eq!(history.search("cargo", 2), Some(0));
+            assert_eq!(history.search("cargo", 0), None);
+            assert_eq!(history.search("c", 99), Some(3));
+            assert_eq!(history.search("git", 4), None);
+        }
+
+        #[test]
+        fn history_round_trips_through_a_file() {
+            let path: PathBuf =
+                std::env::temp_dir().join(format!("cli_tools_history_{}", std::process::id()));
+            let mut history: History = History::new(10);
+            history.load(&path).unwrap();
+            assert!(history.is_empty());
+            history.add("one");
+            history.add("two");
+            history.save(&path).unwrap();
+            let mut loaded: History = History::new(1);
+            loaded.load(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+            assert_eq!(loaded.entries(), ["two"]);
+        }
+
+        #[test]
+        fn word_completer_completes_the_last_word() {
+            let completer: WordCompleter = WordCompleter::new(&["help", "hello", "quit"]);
+            assert_eq!(
+                completer.complete("say hel", 7),
+                (4, vec![String::from("help"), String::from("hello")])
+            );
+            assert_eq!(completer.complete("q", 1), (0, vec![String::from("quit")]));
+            assert_eq!(
+                completer.complete("é\u{3000}q", 6),
+                (5, vec![String::from("quit")])
+            );
+            assert_eq!(completer.complete("x", 1), (0, Vec::new()));
+        }
+
+        #[test]
+        fn consecutive_kills_are_joined_and_yanked() {
+            let mut editor: Editor = Editor::new();
+            editor.kill(String::from("b"), false, false);
+            editor.kill(String::from("c"), false, true);
+            editor.kill(String::from("a"), true, true);
+            assert_eq!(editor.kill_buffer, "abc");
+            editor.kill(String::from("x"), true, false);
+            assert_eq!(editor.kill_buffer, "x");
+            let mut buffer: Buffer = buffer("12", 1);
+            buffer.insert(&editor.kill_buffer);
+            assert_eq!((buffer.line(), buffer.cursor), (String::from("1x2"), 2));
+        }
+
+        #[test]
+        fn completion_extends_the_word_to_the_common_prefix() {
+            let completer: WordCompleter = WordCompleter::new(&["help", "hello", "quit"]);
+            assert_eq!(complete(completer.clone(), "he").0, "hel");
+            assert_eq!(complete(completer.clone(), "q").0, "quit");
+            let (line, output) = complete(completer.clone(), "hel");
+            assert_eq!(line, "hel");
+            assert!(output.contains("help") && output.contains("hello"));
+            assert_eq!(
+                complete(completer, "x"),
+                (String::from("x"), String::from("\x07"))
+            );
+            let words = |_: &str, _: usize| (0, vec![String::from("éa"), String::from("é")]);
+            assert_eq!(complete(words, "").0, "é");
+        }
+
+        #[test]
+        fn completion_clamps_the_offset_of_the_completer() {
+            let inside_a_char = |_: &str, _: usize| (2, vec![String::from("xyz")]);
+            assert_eq!(complete(inside_a_char, "aéb").0, "axyz");
+            let past_the_cursor = |_: &str, _: usize| (99, vec![String::from("!")]);
+            assert_eq!(complete(past_the_cursor, "ab").0, "ab!");
+        }
+    }
+}
+
+/// A module for printing data in rows and columns. The width of each column is measured with
+/// ```text::visible_width()```, so cells may contain styles and wide chars. Tables that are
+/// wider than the terminal are shrunk by truncating or wrapping the widest columns. Tables can
+/// also be written as CSV, TSV, Markdown or JSON, see ```table::Format```.
+pub mod table {
+
+    use crate::term;
+    use crate::text::{self, Style};
+    use std::fmt;
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    /// An enum of the ways text can be aligned within a column.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum Align {
+        #[default]
+        Left,
+        Center,
+        Right,
+    }
+
+    /// An enum of the borders a table can be drawn with.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Border {
+        /// Lines drawn with ```+```, ```-``` and ```|```.
+        Ascii,
+        /// Single lines drawn with box drawing chars, e.g. ```┌─┬─┐```.
+        Single,
+        /// Double lines drawn with box drawing chars, e.g. ```╔═╦═╗```.
+        Double,
+        /// Single lines with rounded corners, e.g. ```╭─┬─╮```.
+        Rounded,
+        /// A GitHub-flavored Markdown table, written in the same way as ```Format::Markdown```.
+        /// Markdown tables are never shrunk to fit.
+        Markdown,
+        /// No lines. Columns are separated by two spaces.
+        None,
+    }
+
+    /// A private struct that holds the chars of a border. Each horizontal line is made of a
+    /// left end, a fill char, a junction between columns and a right end.
+    struct Lines {
+        top: Option<[&'static str; 4]>,
+        separator: Option<[&'static str; 4]>,
+        bottom: Option<[&'static str; 4]>,
+        vertical: &'static str,
+    }
+
+    impl Border {
+        /// A private function that returns the chars used to draw the border.
+        fn lines(self) -> Lines {
+            let (top, separator, bottom, vertical) = match self {
+                Border::Ascii => (
+                    ["+", "-", "+", "+"],
+                    ["+", "-", "+", "+"],
+                    ["+", "-", "+", "+"],
+                    "|",
+                ),
+                Border::Single => (
+                    ["┌", "─", "┬", "┐"],
+                    ["├", "─", "┼", "┤"],
+                    ["└", "─", "┴", "┘"],
+                    "│",
+                ),
+                Border::Double => (
+                    ["╔", "═", "╦", "╗"],
+                    ["╠", "═", "╬", "╣"],
+                    ["╚", "═", "╩", "╝"],
+                    "║",
+                ),
+                Border::Rounded => (
+                    ["╭", "─", "┬", "╮"],
+                    ["├", "─", "┼", "┤"],
+                    ["╰", "─", "┴", "╯"],
+                    "│",
+                ),
+                Border::Markdown => {
+                    return Lines {
+                        top: None,
+                        separator: None,
+                        bottom: None,
+                        vertical: "|",
+                    }
+                }
+                Border::None => {
+                    return Lines {
+                        top: None,
+                        separator: None,
+                        bottom: None,
+                        vertical: "",
+                    }
+                }
+            };
+            Lines {
+                top: Some(top),
+                separator: Some(separator),
+                bottom: Some(bottom),
+                vertical,
+            }
+        }
+
+        /// A private function that returns the number of columns taken up by the border and
+        /// the padding of a table with ```n``` columns.
+        fn overhead(self, n: usize) -> usize {
+            match self {
+                Border::None => 2 * n.saturating_sub(1),
+                _ => 3 * n + 1,
+            }
+        }
+    }
+
+    /// An enum of the ways cells are shrunk when a table is too wide.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Overflow {
+        /// Cut cells off and end them with '…'.
+        Truncate,
+        /// Break cells into several lines.
+        Wrap,
+    }
+
+    /// An enum of the formats a table can be written in. Every format except ```Format::Table```
+    /// removes styles from the cells. A format can be parsed from its name, e.g. the value of a
+    /// ```--format``` command line option.
+    ///
+    /// ```
+    /// use cli_tools::table::{Format, Table};
+    ///
+    /// let mut table = Table::new();
+    /// table.set_headers(["name", "note"]);
+    /// table.add_row(["a", "say \"hi\", then leave"]);
+    /// let format: Format = "csv".parse().unwrap();
+    /// assert_eq!(table.render(format), "name,note\na,\"say \"\"hi\"\", then leave\"\n");
+    /// assert_eq!(
+    ///     table.render(Format::Json),
+    ///     "[\n  {\"name\": \"a\", \"note\": \"say \\\"hi\\\", then leave\"}\n]\n"
+    /// );
+    /// ```
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Format {
+        /// The table as it is printed on the terminal.
+        Table,
+        /// Comma-separated values. Fields that contain commas, quotes or line breaks are quoted,
+        /// with quotes doubled, as in RFC 4180. Lines end with a newline.
+        Csv,
+        /// Tab-separated values. Tabs, line breaks and backslashes in fields are escaped as
+        /// ```\t```, ```\n```, ```\r``` and ```\\```.
+        Tsv,
+        /// A GitHub-flavored Markdown table. Pipes in cells are escaped and line breaks are
+        /// replaced with ```<br>```.
+        Markdown,
+        /// A JSON array with one object per row, keyed by the headers. Columns past the last
+        /// header are keyed by their position, e.g. ```"column4"```. If the table has no headers,
+        /// then each row is an array instead. All values are strings.
+        Json,
+    }
+
+    impl Format {
+        /// All formats, in the order they are listed in error messages.
+        pub const ALL: [Format; 5] = [
+            Format::Table,
+            Format::Csv,
+            Format::Tsv,
+            Format::Markdown,
+            Format::Json,
+        ];
+
+        /// Returns the name of the format, which ```Format::from_str()``` accepts.
+        pub fn name(self) -> &'static str {
+            match self {
+                Format::Table => "table",
+                Format::Csv => "csv",
+                Format::Tsv => "tsv",
+                Format::Markdown => "markdown",
+                Format::Json => "json",
+            }
+        }
+    }
+
+    impl Display for Format {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.name())
+        }
+    }
+
+    impl FromStr for Format {
+        type Err = ParseFormatError;
+
+        /// Parses the name of a format, ignoring case. ```md``` is accepted for Markdown.
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let name: String = s.trim().to_lowercase();
+            if name == "md" {
+                return Ok(Format::Markdown);
+            }
+            Format::ALL
+                .into_iter()
+                .find(|format| format.name() == name)
+                .ok_or_else(|| ParseFormatError(String::from(s)))
+        }
+    }
+
+    /// An error returned when a string is not the name of a ```Format```.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct ParseFormatError(String);
+
+    impl Display for ParseFormatError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let names: Vec<&str> = Format::ALL.iter().map(|format| format.name()).collect();
+            write!(
+                f,
+                "unknown format \"{}\", expected one of {}",
+                self.0,
+                names.join(", ")
+            )
+        }
+    }
+
+    impl std::error::Error for ParseFormatError {}
+
+    /// A private function that quotes a CSV field if needed.
+    fn csv_field(field: &str) -> String {
+        if field.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            String::from(field)
+        }
+    }
+
+    /// A private function that escapes a TSV field.
+    fn tsv_field(field: &str) -> String {
+        let mut escaped: String = String::with_capacity(field.len());
+        for c in field.chars() {
+            match c {
+                '\\' => escaped.push_str("\\\\"),
+                '\t' => escaped.push_str("\\t"),
+                '\n' => escaped.push_str("\\n"),
+                '\r' => escaped.push_str("\\r"),
+                _ => escaped.push(c),
+            }
+        }
+        escaped
+    }
+
+    /// A private function that escapes a Markdown table cell.
+    fn markdown_cell(cell: &str) -> String {
+        cell.replace('\\', "\\\\")
+            .replace('|', "\\|")
+            .replace("\r\n", "<br>")
+            .replace('\n', "<br>")
+    }
+
+    /// A private function that writes ```s``` as a JSON string.
+    fn json_string(s: &str) -> String {
+        let mut json: String = String::with_capacity(s.len() + 2);
+        json.push('"');
+        for c in s.chars() {
+            match c {
+                '"' => json.push_str("\\\""),
+                '\\' => json.push_str("\\\\"),
+                '\n' => json.push_str("\\n"),
+                '\r' => json.push_str("\\r"),
+                '\t' => json.push_str("\\t"),
+                c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+                c => json.push(c),
+            }
+        }
+        json.push('"');
+        json
+    }
+
+    /// A table of rows and columns with an optional header row. Cells can be any type that
+    /// implements ```Display```. Rows that are shorter than the others are filled with empty
+    /// cells.
+    ///
+    /// ```
+    /// use cli_tools::table::{Align, Border, Table};
+    ///
+    /// let mut table = Table::new();
+    /// table.set_headers(["Name", "Size"]);
+    /// table.add_row(["Cargo.toml", "1.2 KiB"]);
+    /// table.add_row(["src", "96 KiB"]);
+    /// table.set_align(1, Align::Right);
+    /// table.set_border(Border::Ascii);
+    /// table.set_header_style(None);
+    /// assert_eq!(
+    ///     table.to_string(),
+    ///     "+------------+---------+\n\
+    ///      | Name       |    Size |\n\
+    ///      +------------+---------+\n\
+    ///      | Cargo.toml | 1.2 KiB |\n\
+    ///      | src        |  96 KiB |\n\
+    ///      +------------+---------+\n"
+    /// );
+    /// ```
+    #[derive(Debug, Clone)]
+    pub struct Table {
+        headers: Vec<String>,
+        rows: Vec<Vec<String>>,
+        aligns: Vec<Align>,
+        border: Border,
+        header_style: Option<Style>,
+        overflow: Overflow,
+        max_width: Option<usize>,
+    }
+
+    impl Default for Table {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl Table {
+        /// Creates an empty table with ```Border::Single```, headers in ```Style::Bold```, and
+        /// cells that are truncated when the table is wider than the terminal.
+        pub fn new() -> Self {
+            Self {
+                headers: Vec::new(),
+                rows: Vec::new(),
+                aligns: Vec::new(),
+                border: Border::Single,
+                header_style: Some(Style::Bold),
+                overflow: Overflow::Truncate,
+                max_width: None,
+            }
+        }
+
+        /// Returns the headers. The table has no header row if this is empty.
+        pub fn headers(&self) -> &[String] {
+            &self.headers
+        }
+
+        /// Sets the headers.
+        pub fn set_headers<I, S>(&mut self, headers: I)
+        where
+            I: IntoIterator<Item = S>,
+            S: Display,
+        {
+            self.headers = headers.into_iter().map(|h| h.to_string()).collect();
+        }
+
+        /// Returns the rows.
+        pub fn rows(&self) -> &[Vec<String>] {
+            &self.rows
+        }
+
+        /// Adds a row to the end of the table.
+        pub fn add_row<I, S>(&mut self, row: I)
+        where
+            I: IntoIterator<Item = S>,
+            S: Display,
+        {
+            self.rows
+                .push(row.into_iter().map(|c| c.to_string()).collect());
+        }
+
+        /// Returns the number of columns, which is the length of the header or the longest row.
+        pub fn column_count(&self) -> usize {
+            self.rows
+                .iter()
+                .map(Vec::len)
+                .fold(self.headers.len(), usize::max)
+        }
+
+        /// Returns the alignment of the column at ```column```.
+        pub fn align(&self, column: usize) -> Align {
+            self.aligns.get(column).copied().unwrap_or_default()
+        }
+
+        /// Sets the alignment of the column at ```column```. Columns are aligned left by default.
+        pub fn set_align(&mut self, column: usize, align: Align) {
+            if self.aligns.len() <= column {
+                self.aligns.resize(column + 1, Align::Left);
+            }
+            self.aligns[column] = align;
+        }
+
+        /// Returns the border.
+        pub fn border(&self) -> Border {
+            self.border
+        }
+
+        /// Sets the border.
+        pub fn set_border(&mut self, border: Border) {
+            self.border = border;
+        }
+
+        /// Returns the text::Style of the headers, if any.
+        pub fn header_style(&self) -> Option<Style> {
+            self.header_style
+        }
+
+        /// Sets the text::Style of the headers. Pass ```None``` to print them like other cells.
+        pub fn set_header_style(&mut self, style: Option<Style>) {
+            self.header_style = style;
+        }
+
+        /// Returns how cells are shrunk when the table is too wide.
+        pub fn overflow(&self) -> Overflow {
+            self.overflow
+        }
+
+        /// Sets how cells are shrunk when the table is too wide.
+        pub fn set_overflow(&mut self, overflow: Overflow) {
+            self.overflow = overflow;
+        }
+
+        /// Returns the maximum width of the table in columns, if any.
+        pub fn max_width(&self) -> Option<usize> {
+            self.max_width
+        }
+
+        /// Sets the maximum width of the table in columns. Pass ```None``` to use the width of
+        /// the terminal, if there is one.
+        pub fn set_max_width(&mut self, max_width: Option<usize>) {
+            self.max_width = max_width;
+        }
+
+        /// A private function that returns the width of each column, shrunk to fit the maximum
+        /// width or the terminal. The widest columns are shrunk first, but not below 3 columns.
+        fn widths(&self, n: usize) -> Vec<usize> {
+            let cell_width =
+                |cell: &str| cell.split('\n').map(text::visible_width).max().unwrap_or(0);
+            let mut widths: Vec<usize> = vec![0; n];
+            for row in std::iter::once(&self.headers).chain(&self.rows) {
+                for (width, cell) in widths.iter_mut().zip(row) {
+                    *width = usize::max(*width, cell_width(cell));
+                }
+            }
+            if self.border == Border::Markdown {
+                widths.iter_mut().for_each(|w| *w = usize::max(*w, 3));
+                return widths;
+            }
+            let limit: Option<usize> = self
+                .max_width
+                .or_else(|| term::size().map(|size| size.cols as usize));
+            let available: usize = match limit {
+                Some(limit) => limit.saturating_sub(self.border.overhead(n)),
+                None => return widths,
+            };
+            let minimums: Vec<usize> = widths.iter().map(|&w| w.min(3)).collect();
+            while widths.iter().sum::<usize>() > available {
+                let widest: Option<usize> = (0..n)
+                    .filter(|&i| widths[i] > minimums[i])
+                    .max_by_key(|&i| (widths[i], std::cmp::Reverse(i)));
+                match widest {
+                    Some(i) => widths[i] -= 1,
+                    None => break,
+                }
+            }
+            widths
+        }
+
+        /// A private function that breaks a cell into the lines it is printed on. Like
+        /// ```text::wrap()```, styles that are active at the end of a line are reset there and
+        /// applied again at the start of the next line.
+        fn cell_lines(&self, cell: &str, width: usize) -> Vec<String> {
+            match self.overflow {
+                Overflow::Wrap if text::visible_width(cell) > width => text::wrap(cell, width),
+                _ => {
+                    let mut active: String = String::new();
+                    cell.split('\n')
+                        .map(|line| {
+                            let styled: String = format!("{}{}", active, line);
+                            text::track_styles(&mut active, line);
+                            let mut line: String = text::truncate(&styled, width);
+                            if !active.is_empty() && !line.ends_with("\x1b[0m") {
+                                line.push_str("\x1b[0m");
+                            }
+                            line
+                        })
+                        .collect()
+                }
+            }
+        }
+
+        /// A private function that writes a horizontal line.
+        fn write_line(
+            f: &mut fmt::Formatter<'_>,
+            chars: [&str; 4],
+            widths: &[usize],
+        ) -> fmt::Result {
+            f.write_str(chars[0])?;
+            for (i, width) in widths.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(chars[2])?;
+                }
+                f.write_str(&chars[1].repeat(width + 2))?;
+            }
+            writeln!(f, "{}", chars[3])
+        }
+
+        /// A private function that writes the separator of a Markdown table, which shows the
+        /// alignment of each column with colons.
+        fn write_markdown_separator(
+            &self,
+            f: &mut fmt::Formatter<'_>,
+            widths: &[usize],
+        ) -> fmt::Result {
+            f.write_str("|")?;
+            for (i, width) in widths.iter().enumerate() {
+                let dashes: String = "-".repeat(*width);
+                match self.align(i) {
+                    Align::Left => write!(f, ":{}-|", dashes)?,
+                    Align::Center => write!(f, ":{}:|", dashes)?,
+                    Align::Right => write!(f, "-{}:|", dashes)?,
+                }
+            }
+            writeln!(f)
+        }
+
+        /// A private function that writes a row, which may take up several lines.
+        fn write_row(
+            &self,
+            f: &mut fmt::Formatter<'_>,
+            row: &[String],
+            widths: &[usize],
+            style: Option<Style>,
+        ) -> fmt::Result {
+            let vertical: &str = self.border.lines().vertical;
+            let cells: Vec<Vec<String>> = widths
+                .iter()
+                .enumerate()
+                .map(|(i, &width)| self.cell_lines(row.get(i).map_or("", String::as_str), width))
+                .collect();
+            let height: usize = cells.iter().map(Vec::len).max().unwrap_or(1);
+            for line in 0..height {
+                let mut text: String = String::new();
+                for (i, &width) in widths.iter().enumerate() {
+                    let cell: &str = cells[i].get(line).map_or("", String::as_str);
+                    let padding: usize = width.saturating_sub(text::visible_width(cell));
+                    let (left, right) = match self.align(i) {
+                        Align::Left => (0, padding),
+                        Align::Center => (padding / 2, padding - padding / 2),
+                        Align::Right => (padding, 0),
+                    };
+                    if self.border == Border::None {
+                        text.push_str(if i > 0 { "  " } else { "" });
+                    } else {
+                        text.push_str(vertical);
+                        text.push(' ');
+                    }
+                    text.push_str(&" ".repeat(left));
+                    match style {
+                        Some(style) if !cell.is_empty() => {
+                            text.push_str(&format!("{}{}{}", style, cell, Style::Regular))
+                        }
+                        _ => text.push_str(cell),
+                    }
+                    text.push_str(&" ".repeat(right));
+                    if self.border != Border::None {
+                        text.push(' ');
+                    }
+                }
+                text.push_str(vertical);
+                if self.border == Border::None {
+                    text.truncate(text.trim_end_matches(' ').len());
+                }
+                writeln!(f, "{}", text)?;
+            }
+            Ok(())
+        }
+
+        /// Prints the table to stdout.
+        pub fn print(&self) {
+            print!("{}", self);
+        }
+
+        /// Writes the table in ```format```. Every line ends with a newline.
+        pub fn render(&self, format: Format) -> String {
+            match format {
+                Format::Table => self.to_string(),
+                Format::Csv => self.to_delimited(',', csv_field),
+                Format::Tsv => self.to_delimited('\t', tsv_field),
+                Format::Markdown => self.to_markdown(),
+                Format::Json => self.to_json(),
+            }
+        }
+
+        /// Prints the table to stdout in ```format```.
+        pub fn print_as(&self, format: Format) {
+            print!("{}", self.render(format));
+        }
+
+        /// A private function that returns the header and the rows without styles, padded with
+        /// empty cells to the same length.
+        fn plain_rows(&self) -> (Option<Vec<String>>, Vec<Vec<String>>) {
+            let n: usize = self.column_count();
+            let plain = |row: &Vec<String>| -> Vec<String> {
+                (0..n)
+                    .map(|i| row.get(i).map_or(String::new(), |c| text::strip_escapes(c)))
+                    .collect()
+            };
+            let headers: Option<Vec<String>> = if self.headers.is_empty() {
+                None
+            } else {
+                Some(plain(&self.headers))
+            };
+            (headers, self.rows.iter().map(plain).collect())
+        }
+
+        /// A private function that writes the table as lines of fields separated by
+        /// ```separator```.
+        fn to_delimited(&self, separator: char, field: fn(&str) -> String) -> String {
+            let (headers, rows) = self.plain_rows();
+            let mut result: String = String::new();
+            for row in headers.iter().chain(&rows) {
+                let fields: Vec<String> = row.iter().map(|c| field(c)).collect();
+                result.push_str(&fields.join(&separator.to_string()));
+                result.push('\n');
+            }
+            result
+        }
+
+        /// A private function that writes the table as a Markdown table.
+        fn to_markdown(&self) -> String {
+            let mut table: Table = self.clone();
+            table.set_border(Border::Markdown);
+            table.to_string()
+        }
+
+        /// A private function that writes the table as a Markdown table without styles and with
+        /// pipes and line breaks in cells escaped. A table without headers gets an empty header
+        /// row, because Markdown tables require one.
+        fn write_markdown(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let (headers, rows) = self.plain_rows();
+            let escape = |row: &Vec<String>| -> Vec<String> {
+                row.iter().map(|c| markdown_cell(c)).collect()
+            };
+            let table: Table = Table {
+                headers: headers
+                    .as_ref()
+                    .map_or(vec![String::new(); self.column_count()], escape),
+                rows: rows.iter().map(escape).collect(),
+                aligns: self.aligns.clone(),
+                border: Border::Markdown,
+                header_style: None,
+                overflow: Overflow::Truncate,
+                max_width: None,
+            };
+            let widths: Vec<usize> = table.widths(table.column_count());
+            table.write_row(f, &table.headers, &widths, None)?;
+            table.write_markdown_separator(f, &widths)?;
+            for row in &table.rows {
+                table.write_row(f, row, &widths, None)?;
+            }
+            Ok(())
+        }
+
+        /// A private function that writes the table as a JSON array. The padding that
+        /// ```Table::plain_rows()``` adds to the header is replaced with the position of the
+        /// column, so that every key of an object is unique.
+        fn to_json(&self) -> String {
+            let (headers, rows) = self.plain_rows();
+            if rows.is_empty() {
+                return String::from("[]\n");
+            }
+            let headers: Option<Vec<String>> = headers.map(|headers| {
+                headers
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, key)| {
+                        if i < self.headers.len() {
+                            key
+                        } else {
+                            format!("column{}", i + 1)
+                        }
+                    })
+                    .collect()
+            });
+            let mut result: String = String::from("[\n");
+            for (i, row) in rows.iter().enumerate() {
+                let values: Vec<String> = match &headers {
+                    Some(headers) => headers
+                        .iter()
+                        .zip(row)
+                        .map(|(key, value)| format!("{}: {}", json_string(key), json_string(value)))
+                        .collect(),
+                    None => row.iter().map(|value| json_string(value)).collect(),
+                };
+                let (open, close) = if headers.is_some() {
+                    ('{', '}')
+                } else {
+                    ('[', ']')
+                };
+                let comma: &str = if i + 1 < rows.len() { "," } else { "" };
+                result.push_str(&format!(
+                    "  {}{}{}{}\n",
+                    open,
+                    values.join(", "),
+                    close,
+                    comma
+                ));
+            }
+            result.push_str("]\n");
+            result
+        }
+    }
+
+    impl Display for Table {
+        /// Formats the table as lines of text, each ending with a newline.
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let n: usize = self.column_count();
+            if n == 0 {
+                return Ok(());
+            }
+            if self.border == Border::Markdown {
+                return self.write_markdown(f);
+            }
+            let widths: Vec<usize> = self.widths(n);
+            let lines: Lines = self.border.lines();
+            if let Some(top) = lines.top {
+                Self::write_line(f, top, &widths)?;
+            }
+            if !self.headers.is_empty() {
+                self.write_row(f, &self.headers, &widths, self.header_style)?;
+                if let Some(separator) = lines.separator {
+                    Self::write_line(f, separator, &widths)?;
+                }
+            }
+            for row in &self.rows {
+                self.write_row(f, row, &widths, None)?;
+            }
+            if let Some(bottom) = lines.bottom {
+                Self::write_line(f, bottom, &widths)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+
+        use super::*;
+
+        fn table(border: Border, max_width: usize) -> Table {
+            let mut table: Table = Table::new();
+            table.set_headers(["name", "description"]);
+            table.add_row(["short", "a much longer description"]);
+            table.set_border(border);
+            table.set_header_style(None);
+            table.set_max_width(Some(max_width));
+            table
+        }
+
+        #[test]
+        fn csv_fields_are_quoted_when_needed() {
+            assert_eq!(csv_field("plain"), "plain");
+            assert_eq!(csv_field("a,b"), "\"a,b\"");
+            assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+            assert_eq!(csv_field("a\nb"), "\"a\nb\"");
+        }
+
+        #[test]
+        fn tsv_fields_are_escaped() {
+            assert_eq!(tsv_field("a\tb\\c\r\n"), "a\\tb\\\\c\\r\\n");
+        }
+
+        #[test]
+        fn markdown_cells_are_escaped() {
+            assert_eq!(markdown_cell("a|b\\c"), "a\\|b\\\\c");
+            assert_eq!(markdown_cell("a\r\nb\nc"), "a<br>b<br>c");
+        }
+
+        #[test]
+        fn json_strings_are_escaped() {
+            assert_eq!(
+                json_string("a\"b\\c\n\t\u{1}é"),
+                "\"a\\\"b\\\\c\\n\\t\\u0001é\""
+            );
+        }
+
+        #[test]
+        fn widths_shrink_the_widest_column_first() {
+            assert_eq!(table(Border::Single, 100).widths(2), [5, 25]);
+            // 20 columns minus the border and padding of 7 leaves 13
+            assert_eq!(table(Border::Single, 20).widths(2), [5, 8]);
+            assert_eq!(table(Border::None, 15).widths(2), [5, 8]);
+            assert_eq!(table(Border::Single, 5).widths(2), [3, 3]);
+        }
+
+        #[test]
+        fn markdown_tables_are_never_shrunk() {
+            assert_eq!(table(Border::Markdown, 5).widths(2), [5, 25]);
+        }
+
+        #[test]
+        fn shrunk_tables_fit_the_maximum_width() {
+            for overflow in [Overflow::Truncate, Overflow::Wrap] {
+                let mut table: Table = table(Border::Single, 20);
+                table.set_overflow(overflow);
+                let text: String = table.to_string();
+                assert!(
+                    text.lines().all(|line| text::visible_width(line) <= 20),
+                    "{}",
+                    text
+                );
+            }
+        }
+
+        #[test]
+        fn truncated_cells_keep_their_styles_to_themselves() {
+            let table: Table = Table::new();
+            let bold: String = Style::Bold.to_string();
+            let cell: String = format!("{}abcdef\nxy", bold);
+            assert_eq!(
+                table.cell_lines(&cell, 4),
+                [
+                    format!("{}abc\u{2026}\x1b[0m", bold),
+                    format!("{}xy\x1b[0m", bold)
+                ]
+            );
+            let cell: String = format!("{}ab\ncd{}ef", bold, Style::Regular);
+            assert_eq!(
+                table.cell_lines(&cell, 10),
+                [
+                    format!("{}ab\x1b[0m", bold),
+                    format!("{}cd{}ef", bold, Style::Regular)
+                ]
+            );
+        }
+
+        #[test]
+        fn formats_escape_their_special_chars() {
+            let mut table: Table = Table::new();
+            table.set_headers(["k", "v"]);
+            table.add_row([
+                format!("{}a|b{}", Style::Bold, Style::Regular),
+                String::from("x,\"y\""),
+            ]);
+            assert_eq!(table.render(Format::Csv), "k,v\na|b,\"x,\"\"y\"\"\"\n");
+            assert_eq!(table.render(Format::Tsv), "k\tv\na|b\tx,\"y\"\n");
+            assert_eq!(
+                table.render(Format::Markdown),
+                "| k    | v     |\n|:-----|:------|\n| a\\|b | x,\"y\" |\n"
+            );
+            assert_eq!(
+                table.render(Format::Json),
+                "[\n  {\"k\": \"a|b\", \"v\": \"x,\\\"y\\\"\"}\n]\n"
+            );
+        }
+
+        #[test]
+        fn json_keys_columns_past_the_headers_by_position() {
+            let mut table: Table = Table::new();
+            table.set_headers(["a", "b"]);
+            table.add_row(["1"]);
+            table.add_row(["1", "2", "3", "4"]);
+            assert_eq!(
+                table.render(Format::Json),
+                "[\n  {\"a\": \"1\", \"b\": \"\", \"column3\": \"\", \"column4\": \"\"},\n  \
+                 {\"a\": \"1\", \"b\": \"2\", \"column3\": \"3\", \"column4\": \"4\"}\n]\n"
+            );
+        }
+
+        #[test]
+        fn markdown_tables_without_headers_get_an_empty_header_row() {
+            let mut table: Table = Table::new();
+            table.add_row(["a"]);
+            table.set_border(Border::Markdown);
+            assert_eq!(table.to_string(), "|     |\n|:----|\n| a   |\n");
+            assert_eq!(table.to_string(), table.render(Format::Markdown));
+        }
+    }
+}
+
+/// A module for printing hierarchical data as a tree, like the ```tree``` command does for
+/// directories. Each node has a label and an optional ```text::Style```, and nodes are joined by
+/// box drawing connectors. ```DirWalker``` builds a tree from a directory on the filesystem.
+pub mod tree {
+
+    use crate::format::BinaryBytes;
+    use crate::text::Style;
+    use std::fmt;
+    use std::fmt::Display;
+    use std::fs;
+    use std::io;
+    use std::path::Path;
+
+    const BRANCH: &str = "├── ";
+    const LAST_BRANCH: &str = "└── ";
+    const PIPE: &str = "│   ";
+    const SPACE: &str = "    ";
+
+    /// A node of a tree with a label, an optional style and any number of children.
+    #[derive(Debug, Clone)]
+    pub struct Node {
+        label: String,
+        style: Option<Style>,
+        children: Vec<Node>,
+    }
+
+    impl Node {
+        /// Creates a new node without a style or children.
+        pub fn new(label: &str) -> Self {
+            Self {
+                label: String::from(label),
+                style: None,
+                children: Vec::new(),
+            }
+        }
+
+        /// Returns the label.
+        pub fn label(&self) -> &str {
+            &self.label
+        }
+
+        /// Sets the label. A label with several lines is printed with the following lines
+        /// indented below the first.
+        pub fn set_label(&mut self, label: &str) {
+            self.label = String::from(label);
+        }
+
+        /// Returns the text::Style of the label, if any.
+        pub fn style(&self) -> Option<Style> {
+            self.style
+        }
+
+        /// Sets the text::Style of the label. Pass ```None``` to print the label as it is.
+        pub fn set_style(&mut self, style: Option<Style>) {
+            self.style = style;
+        }
+
+        /// Returns the children.
+        pub fn children(&self) -> &[Node] {
+            &self.children
+        }
+
+        /// Returns the children so they can be changed or sorted.
+        pub fn children_mut(&mut self) -> &mut Vec<Node> {
+            &mut self.children
+        }
+
+        /// Adds ```child``` as the last child and returns a reference to it, so that children
+        /// can be added to it in turn.
+        pub fn add_child(&mut self, child: Node) -> &mut Node {
+            self.children.push(child);
+            self.children.last_mut().unwrap()
+        }
+
+        /// Returns the number of nodes in the tree below and including this node.
+        pub fn count(&self) -> usize {
+            1 + self.children.iter().map(Node::count).sum::<usize>()
+        }
+    }
+
+    /// A tree of ```Node``` values that implements ```Display```.
+    ///
+    /// ```
+    /// use cli_tools::tree::{Node, Tree};
+    ///
+    /// let mut root = Node::new("deploy");
+    /// let staging = root.add_child(Node::new("staging"));
+    /// staging.add_child(Node::new("web-01"));
+    /// root.add_child(Node::new("production"));
+    /// assert_eq!(
+    ///     Tree::new(root).to_string(),
+    ///     "deploy\n├── staging\n│   └── web-01\n└── production\n"
+    /// );
+    /// ```
+    #[derive(Debug, Clone)]
+    pub struct Tree {
+        root: Node,
+        max_depth: Option<usize>,
+        guide_style: Option<Style>,
+    }
+
+    impl Tree {
+        /// Creates a new tree that shows all levels below ```root```.
+        pub fn new(root: Node) -> Self {
+            Self {
+                root,
+                max_depth: None,
+                guide_style: None,
+            }
+        }
+
+        /// Returns the root node.
+        pub fn root(&self) -> &Node {
+            &self.root
+        }
+
+        /// Returns the root node so that nodes can be changed.
+        pub fn root_mut(&mut self) -> &mut Node {
+            &mut self.root
+        }
+
+        /// Returns the number of levels shown below the root, if limited.
+        pub fn max_depth(&self) -> Option<usize> {
+            self.max_depth
+        }
+
+        /// Sets the number of levels shown below the root. ```Some(0)``` shows only the root and
+        /// ```None``` shows all levels.
+        pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+            self.max_depth = max_depth;
+        }
+
+        /// Returns the text::Style of the connectors, if any.
+        pub fn guide_style(&self) -> Option<Style> {
+            self.guide_style
+        }
+
+        /// Sets the text::Style of the connectors, e.g. ```Style::Faint``` to make the labels
+        /// stand out.
+        pub fn set_guide_style(&mut self, style: Option<Style>) {
+            self.guide_style = style;
+        }
+
+        /// Prints the tree to stdout.
+        pub fn print(&self) {
+            print!("{}", self);
+        }
+
+        /// A private function that writes ```guide``` in the guide style.
+        fn write_guide(&self, f: &mut fmt::Formatter<'_>, guide: &str) -> fmt::Result {
+            match self.guide_style {
+                Some(style) if !guide.is_empty() => {
+                    write!(f, "{}{}{}", style, guide, Style::Regular)
+                }
+                _ => f.write_str(guide),
+            }
+        }
+
+        /// A private function that writes ```node``` and its children. ```prefix``` holds the
+        /// guides of the levels above, ```connector``` joins the node to its parent and
+        /// ```indent``` is printed in front of the following lines of the label.
+        fn write_node(
+            &self,
+            f: &mut fmt::Formatter<'_>,
+            node: &Node,
+            prefix: &str,
+            (connector, indent): (&str, &str),
+            depth: usize,
+        ) -> fmt::Result {
+            for (i, line) in node.label.split('\n').enumerate() {
+                self.write_guide(f, prefix)?;
+                self.write_guide(f, if i == 0 { connector } else { indent })?;
+                match node.style {
+                    Some(style) => writeln!(f, "{}{}{}", style, line, Style::Regular)?,
+                    None => writeln!(f, "{}", line)?,
+                }
+            }
+            if self.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+                return Ok(());
+            }
+            let prefix: String = format!("{}{}", prefix, indent);
+            for (i, child) in node.children.iter().enumerate() {
+                let guides: (&str, &str) = if i + 1 == node.children.len() {
+                    (LAST_BRANCH, SPACE)
+                } else {
+                    (BRANCH, PIPE)
+                };
+                self.write_node(f, child, &prefix, guides, depth + 1)?;
+            }
+            Ok(())
+        }
+    }
+
+    impl Display for Tree {
+        /// Formats the tree as lines of text, each ending with a newline.
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.write_node(f, &self.root, "", ("", ""), 0)
+        }
+    }
+
+    /// Builds a ```Node``` tree from a directory on the filesystem, like the ```tree``` command.
+    /// Entries are sorted by name, with directories first. Directories are printed in
+    /// ```Style::Blue``` and symbolic links in ```Style::Cyan```, followed by their target.
+    /// Symbolic links are never followed.
+    ///
+    /// ```no_run
+    /// use cli_tools::tree::{DirWalker, Tree};
+    /// use std::path::Path;
+    ///
+    /// let mut walker = DirWalker::new();
+    /// walker.set_max_depth(Some(2));
+    /// walker.set_show_sizes(true);
+    /// Tree::new(walker.walk(Path::new(".")).unwrap()).print();
+    /// ```
+    #[derive(Debug, Clone)]
+    pub struct DirWalker {
+        max_depth: Option<usize>,
+        show_hidden: bool,
+        show_sizes: bool,
+        dirs_first: bool,
+        dir_style: Option<Style>,
+        link_style: Option<Style>,
+    }
+
+    impl Default for DirWalker {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl DirWalker {
+        /// Creates a new walker that visits all levels, skips hidden entries, and does not show
+        /// sizes.
+        pub fn new() -> Self {
+            Self {
+                max_depth: None,
+                show_hidden: false,
+                show_sizes: false,
+                dirs_first: true,
+                dir_style: Some(Style::Blue),
+                link_style: Some(Style::Cyan),
+            }
+        }
+
+        /// Returns the number of levels visited below the root, if limited.
+        pub fn max_depth(&self) -> Option<usize> {
+            self.max_depth
+        }
+
+        /// Sets the number of levels visited below the root. Pass ```None``` to visit all levels.
+        pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+            self.max_depth = max_depth;
+        }
+
+        /// Returns ```true``` if entries whose names start with a dot are included.
+        pub fn show_hidden(&self) -> bool {
+            self.show_hidden
+        }
+
+        /// Includes or skips entries whose names start with a dot.
+        pub fn set_show_hidden(&mut self, show_hidden: bool) {
+            self.show_hidden = show_hidden;
+        }
+
+        /// Returns ```true``` if sizes are shown after the names.
+        pub fn show_sizes(&self) -> bool {
+            self.show_sizes
+        }
+
+        /// Shows or hides sizes after the names, formatted with ```format::BinaryBytes```. The
+        /// size of a directory is the total size of the files in it at any level, even below the
+        /// maximum depth, so showing sizes visits the whole directory.
+        pub fn set_show_sizes(&mut self, show_sizes: bool) {
+            self.show_sizes = show_sizes;
+        }
+
+        /// Returns ```true``` if directories are listed before files.
+        pub fn dirs_first(&self) -> bool {
+            self.dirs_first
+        }
+
+        /// Lists directories before files, or mixes them in order of name.
+        pub fn set_dirs_first(&mut self, dirs_first: bool) {
+            self.dirs_first = dirs_first;
+        }
+
+        /// Returns the text::Style of directories, if any.
+        pub fn dir_style(&self) -> Option<Style> {
+            self.dir_style
+        }
+
+        /// Sets the text::Style of directories.
+        pub fn set_dir_style(&mut self, style: Option<Style>) {
+            self.dir_style = style;
+        }
+
+        /// Returns the text::Style of symbolic links, if any.
+        pub fn link_style(&self) -> Option<Style> {
+            self.link_style
+        }
+
+        /// Sets the text::Style of symbolic links.
+        pub fn set_link_style(&mut self, style: Option<Style>) {
+            self.link_style = style;
+        }
+
+        /// Builds a tree of the directory or file at ```path```. The root is labeled with
+        /// ```path``` as given. Returns an error if ```path``` cannot be read. Entries below it
+        /// that cannot be read are labeled with the error instead.
+        pub fn walk(&self, path: &Path) -> io::Result<Node> {
+            let metadata: fs::Metadata = fs::symlink_metadata(path)?;
+            if metadata.is_dir() {
+                fs::read_dir(path)?;
+            }
+            Ok(self.visit(path, path.display().to_string(), &metadata, 0).0)
+        }
+
+        /// A private function that builds the node of one entry and returns it with the total
+        /// size of the entry.
+        fn visit(
+            &self,
+            path: &Path,
+            name: String,
+            metadata: &fs::Metadata,
+            depth: usize,
+        ) -> (Node, u64) {
+            let mut node: Node = Node::new(&name);
+            let mut size: u64 = metadata.len();
+            if metadata.is_symlink() {
+                node.style = self.link_style;
+                if let Ok(target) = fs::read_link(path) {
+                    node.label = format!("{} -> {}", name, target.display());
+                }
+            } else if metadata.is_dir() {
+                node.style = self.dir_style;
+                size = 0;
+                let deeper: bool = match self.max_depth {
+                    Some(max_depth) => depth < max_depth,
+                    None => true,
+                };
+                if deeper || self.show_sizes {
+                    match self.entries(path) {
+                        Ok(entries) => {
+                            for (entry_path, entry_name, entry_metadata) in entries {
+                                let (child, child_size) = match entry_metadata {
+                                    Ok(entry_metadata) => self.visit(
+                                        &entry_path,
+                                        entry_name,
+                                        &entry_metadata,
+                                        depth + 1,
+                                    ),
+                                    Err(e) => (Node::new(&format!("{} [{}]", entry_name, e)), 0),
+                                };
+                                size += child_size;
+                                if deeper {
+                                    node.children.push(child);
+                                }
+                            }
+                        }
+                        Err(e) => node.label = format!("{} [{}]", name, e),
+                    }
+                }
+            }
+            if self.show_sizes {
+                node.label = format!("{} ({})", node.label, BinaryBytes(size));
+            }
+            (node, size)
+        }
+
+        /// A private function that returns the entries of a directory in the order they are shown.
+        /// An entry whose metadata cannot be read is returned with the error, so that one entry
+        /// does not hide the rest of the directory. An entry that cannot be read at all is
+        /// returned as ```?``` with the error.
+        #[allow(clippy::type_complexity)]
+        fn entries(
+            &self,
+            path: &Path,
+        ) -> io::Result<Vec<(std::path::PathBuf, String, io::Result<fs::Metadata>)>> {
+            let mut entries: Vec<(std::path::PathBuf, String, io::Result<fs::Metadata>)> =
+                Vec::new();
+            for entry in fs::read_dir(path)? {
+                let entry: fs::DirEntry = match entry {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        entries.push((path.to_path_buf(), String::from("?"), Err(e)));
+                        continue;
+                    }
+                };
+                let name: String = entry.file_name().to_string_lossy().into_owned();
+                if !self.show_hidden && name.starts_with('.') {
+                    continue;
+                }
+                entries.push((entry.path(), name, entry.metadata()));
+            }
+            let is_dir = |metadata: &io::Result<fs::Metadata>| -> bool {
+                metadata.as_ref().is_ok_and(fs::Metadata::is_dir)
+            };
+            entries.sort_by(|a, b| {
+                let dirs: std::cmp::Ordering = if self.dirs_first {
+                    is_dir(&b.2).cmp(&is_dir(&a.2))
+                } else {
+                    std::cmp::Ordering::Equal
+                };
+                dirs.then_with(|| a.1.cmp(&b.1))
+            });
+            Ok(entries)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+
+        use super::*;
+
+        fn tree() -> Tree {
+            let mut root: Node = Node::new("root");
+            root.add_child(Node::new("a")).add_child(Node::new("a1"));
+            root.add_child(Node::new("b")).add_child(Node::new("b1"));
+            Tree::new(root)
+        }
+
+        #[test]
+        fn connectors_join_the_nodes() {
+            assert_eq!(
+                tree().to_string(),
+                "root\n├── a\n│   └── a1\n└── b\n    └── b1\n"
+            );
+        }
+
+        #[test]
+        fn max_depth_hides_deeper_levels() {
+            let mut tree: Tree = tree();
+            tree.set_max_depth(Some(1));
+            assert_eq!(tree.to_string(), "root\n├── a\n└── b\n");
+            tree.set_max_depth(Some(0));
+            assert_eq!(tree.to_string(), "root\n");
+        }
+
+        #[test]
+        fn labels_with_several_lines_stay_under_their_connector() {
+            let mut root: Node = Node::new("root");
+            root.add_child(Node::new("a\nb"));
+            root.add_child(Node::new("c"));
+            assert_eq!(Tree::new(root).to_string(), "root\n├── a\n│   b\n└── c\n");
+        }
+
+        /// Creates a directory named after ```test``` in the temporary directory, holding:
+        ///
+        /// ```text
+        /// .hidden          5 bytes
+        /// a.txt           10 bytes
+        /// b_dir/
+        ///     deep/
+        ///         big.bin 1000 bytes
+        ///     top.txt     24 bytes
+        /// c_link -> a.txt
+        /// ```
+        fn fixture(test: &str) -> std::path::PathBuf {
+            let root: std::path::PathBuf = std::env::temp_dir().join(format!(
+                "cli_tools_tree_{}_{}",
+                test,
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&root);
+            fs::create_dir_all(root.join("b_dir").join("deep")).unwrap();
+            fs::write(root.join(".hidden"), [0; 5]).unwrap();
+            fs::write(root.join("a.txt"), [0; 10]).unwrap();
+            fs::write(root.join("b_dir").join("deep").join("big.bin"), [0; 1000]).unwrap();
+            fs::write(root.join("b_dir").join("top.txt"), [0; 24]).unwrap();
+            std::os::unix::fs::symlink("a.txt", root.join("c_link")).unwrap();
+            root
+        }
+
+        fn labels(node: &Node) -> Vec<&str> {
+            node.children().iter().map(Node::label).collect()
+        }
+
+        #[test]
+        fn walked_directories_come_before_files_unless_mixed() {
+            let root: std::path::PathBuf = fixture("dirs_first");
+            let mut walker: DirWalker = DirWalker::new();
+            let node: Node = walker.walk(&root).unwrap();
+            assert_eq!(node.label(), root.display().to_string());
+            assert_eq!(labels(&node), ["b_dir", "a.txt", "c_link -> a.txt"]);
+            assert_eq!(labels(&node.children()[0]), ["deep", "top.txt"]);
+            walker.set_dirs_first(false);
+            let node: Node = walker.walk(&root).unwrap();
+            assert_eq!(labels(&node), ["a.txt", "b_dir", "c_link -> a.txt"]);
+            fs::remove_dir_all(&root).unwrap();
+        }
+
+        #[test]
+        fn hidden_entries_are_skipped_unless_shown() {
+            let root: std::path::PathBuf = fixture("hidden");
+            let mut walker: DirWalker = DirWalker::new();
+            walker.set_max_depth(Some(1));
+            walker.set_show_hidden(true);
+            let node: Node = walker.walk(&root).unwrap();
+            assert_eq!(
+                labels(&node),
+                ["b_dir", ".hidden", "a.txt", "c_link -> a.txt"]
+            );
+            fs::remove_dir_all(&root).unwrap();
+        }
+
+        #[test]
+        fn sizes_include_entries_below_the_maximum_depth() {
+            let root: std::path::PathBuf = fixture("sizes");
+            let mut walker: DirWalker = DirWalker::new();
+            walker.set_show_sizes(true);
+            walker.set_max_depth(Some(1));
+            let node: Node = walker.walk(&root).unwrap();
+            let label = |name: &str, size: u64| format!("{} ({})", name, BinaryBytes(size));
+            assert_eq!(
+                labels(&node),
+                [
+                    label("b_dir", 1024),
+                    label("a.txt", 10),
+                    label("c_link -> a.txt", 5)
+                ]
+            );
+            assert!(node.children()[0].children().is_empty());
+            walker.set_max_depth(Some(0));
+            let node: Node = walker.walk(&root).unwrap();
+            assert_eq!(node.label(), label(&root.display().to_string(), 1039));
+            assert!(node.children().is_empty());
+            fs::remove_dir_all(&root).unwrap();
+        }
+
+        #[test]
+        fn symbolic_links_show_their_target_and_are_not_followed() {
+            let root: std::path::PathBuf = fixture("links");
+            fs::remove_file(root.join("c_link")).unwrap();
+            std::os::unix::fs::symlink("b_dir", root.join("c_link")).unwrap();
+            let mut walker: DirWalker = DirWalker::new();
+            walker.set_link_style(Some(Style::Red));
+            let node: Node = walker.walk(&root).unwrap();
+            let link: &Node = &node.children()[2];
+            assert_eq!(link.label(), "c_link -> b_dir");
+            assert!(matches!(link.style(), Some(Style::Red)));
+            assert!(link.children().is_empty());
+            assert!(matches!(node.children()[0].style(), Some(Style::Blue)));
+            fs::remove_dir_all(&root).unwrap();
+        }
+
+        #[test]
+        fn unreadable_directories_are_labeled_with_the_error() {
+            use std::os::unix::fs::PermissionsExt;
+            let root: std::path::PathBuf = fixture("unreadable");
+            let locked: std::path::PathBuf = root.join("b_dir");
+            fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+            let walked: io::Result<Node> = DirWalker::new().walk(&root);
+            let error: Option<io::Error> = fs::read_dir(&locked).err();
+            fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+            fs::remove_dir_all(&root).unwrap();
+            let node: Node = walked.unwrap();
+            // Permissions do not stop a privileged user from reading the directory.
+            match error {
+                Some(error) => {
+                    assert_eq!(node.children()[0].label(), format!("b_dir [{}]", error));
+                    assert!(node.children()[0].children().is_empty());
+                }
+                None => assert_eq!(labels(&node.children()[0]), ["deep", "top.txt"]),
+            }
+            assert_eq!(labels(&node)[1..], ["a.txt", "c_link -> a.txt"]);
+            assert!(DirWalker::new().walk(&root).is_err());
+        }
     }
 }
@@ -47,6 +47,8 @@ fn main() {
             }
 
             if n == 500000 {
+                tx.send(Message::Print(String::from("Half way there...")))
+                    .unwrap();
                 tx.send(Message::TextStyle(Style::Green)).unwrap();
                 tx.send(Message::ShowBrackets(true)).unwrap();
                 tx.send(Message::TrailingChar(BarChar::LowLine)).unwrap();
@@ -65,7 +67,12 @@ fn main() {
     let mut pbar: ProgressBar = ProgressBar::new("My Progress Bar ");
     pbar.set_interval(3);
     pbar.set_style(Style::Italic);
+    pbar.set_smooth(true);
+    pbar.set_label_style(Some(Style::Bold));
+    pbar.set_steady_tick(Some(100));
+    pbar.show_elapsed(true);
     pbar.listen(&rx);
+    pbar.finish_with_message("Done!");
 
-    println!("\nDone working!");
+    println!("Done working!");
 }